@@ -0,0 +1,261 @@
+use attrs::Mtime;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use attrs::FileAttrs;
+use cid::Cid;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use multipart::Multipart;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::{string_field, u64_field};
+#[cfg(feature = "blocking")]
+use response::JsonLines;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::fs;
+#[cfg(feature = "blocking")]
+use std::io::Read;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::path::Path;
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// Options for `add`, mirroring the flags of `ipfs add`. Anything left unset
+/// uses the daemon's default.
+#[derive(Debug, Clone, Default)]
+pub struct AddOptions {
+    chunker: Option<String>,
+    raw_leaves: Option<bool>,
+    cid_version: Option<u32>,
+    hash: Option<String>,
+    trickle: bool,
+    wrap_with_directory: bool,
+    only_hash: bool,
+    pin: Option<bool>,
+    mode: Option<u32>,
+    mtime: Option<Mtime>,
+    preserve_mode: bool,
+    preserve_mtime: bool
+}
+
+impl AddOptions {
+    pub fn new() -> AddOptions {
+        AddOptions::default()
+    }
+
+    /// Chunking algorithm, e.g. `size-262144`, `rabin-262144-524288-1048576`
+    /// or `buzhash`.
+    pub fn chunker(mut self, chunker: &str) -> AddOptions {
+        self.chunker = Some(chunker.to_string());
+        self
+    }
+
+    pub fn raw_leaves(mut self, raw_leaves: bool) -> AddOptions {
+        self.raw_leaves = Some(raw_leaves);
+        self
+    }
+
+    pub fn cid_version(mut self, version: u32) -> AddOptions {
+        self.cid_version = Some(version);
+        self
+    }
+
+    /// Multihash function name, e.g. `sha2-256` or `blake2b-256`.
+    pub fn hash(mut self, hash: &str) -> AddOptions {
+        self.hash = Some(hash.to_string());
+        self
+    }
+
+    pub fn trickle(mut self, trickle: bool) -> AddOptions {
+        self.trickle = trickle;
+        self
+    }
+
+    pub fn wrap_with_directory(mut self, wrap: bool) -> AddOptions {
+        self.wrap_with_directory = wrap;
+        self
+    }
+
+    /// Only compute the hashes; nothing is written to the node's datastore.
+    pub fn only_hash(mut self, only_hash: bool) -> AddOptions {
+        self.only_hash = only_hash;
+        self
+    }
+
+    pub fn pin(mut self, pin: bool) -> AddOptions {
+        self.pin = Some(pin);
+        self
+    }
+
+    /// Permission bits to record for every added file and directory.
+    pub fn mode(mut self, mode: u32) -> AddOptions {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Modification time to record for every added file and directory.
+    pub fn mtime(mut self, mtime: Mtime) -> AddOptions {
+        self.mtime = Some(mtime);
+        self
+    }
+
+    /// Record the mode of each local file uploaded by `add_path`.
+    pub fn preserve_mode(mut self, preserve: bool) -> AddOptions {
+        self.preserve_mode = preserve;
+        self
+    }
+
+    /// Record the modification time of each local file uploaded by
+    /// `add_path`.
+    pub fn preserve_mtime(mut self, preserve: bool) -> AddOptions {
+        self.preserve_mtime = preserve;
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = Vec::new();
+        if let Some(ref chunker) = self.chunker {
+            args.push(("chunker", chunker.clone()));
+        }
+        if let Some(raw_leaves) = self.raw_leaves {
+            args.push(("raw-leaves", raw_leaves.to_string()));
+        }
+        if let Some(version) = self.cid_version {
+            args.push(("cid-version", version.to_string()));
+        }
+        if let Some(ref hash) = self.hash {
+            args.push(("hash", hash.clone()));
+        }
+        if self.trickle {
+            args.push(("trickle", "true".to_string()));
+        }
+        if self.wrap_with_directory {
+            args.push(("wrap-with-directory", "true".to_string()));
+        }
+        if self.only_hash {
+            args.push(("only-hash", "true".to_string()));
+        }
+        if let Some(pin) = self.pin {
+            args.push(("pin", pin.to_string()));
+        }
+        if let Some(mode) = self.mode {
+            args.push(("mode", format!("{:04o}", mode)));
+        }
+        if let Some(mtime) = self.mtime {
+            args.push(("mtime", mtime.seconds.to_string()));
+            args.push(("mtime-nsecs", mtime.nanos.to_string()));
+        }
+        if self.preserve_mode {
+            args.push(("preserve-mode", "true".to_string()));
+        }
+        if self.preserve_mtime {
+            args.push(("preserve-mtime", "true".to_string()));
+        }
+        args
+    }
+}
+
+/// One object created by `add`. Directory uploads produce an entry for every
+/// file and directory, with the root last.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddedObject {
+    pub name: String,
+    pub hash: Cid,
+    pub size: u64
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Adds the contents of `data` as a single file called `name`.
+    pub fn add<R: Read>(&self, name: &str, data: R, options: &AddOptions) -> Result<Vec<AddedObject>, IPFSError> {
+        let mut form = Multipart::new();
+        form.add_reader(name, data);
+        self.add_form(form, options)
+    }
+
+    pub fn add_bytes(&self, name: &str, data: &[u8], options: &AddOptions) -> Result<Vec<AddedObject>, IPFSError> {
+        self.add(name, data, options)
+    }
+
+    /// Adds a local file, or a directory and everything below it. Entries are
+    /// named relative to the parent of `path`, as `ipfs add -r` does.
+    pub fn add_path(&self, path: &Path, options: &AddOptions) -> Result<Vec<AddedObject>, IPFSError> {
+        self.add_form(path_form(path, options)?, options)
+    }
+
+    fn add_form(&self, mut form: Multipart, options: &AddOptions) -> Result<Vec<AddedObject>, IPFSError> {
+        let mut args = options.to_args();
+        args.push(("progress", "false".to_string()));
+        let res = self.request_with_body("add", &args, &mut form)?;
+
+        let mut added = Vec::new();
+        for line in JsonLines::new(res) {
+            if let Some(object) = parse_added(&line?)? {
+                added.push(object);
+            }
+        }
+        Ok(added)
+    }
+}
+
+/// Reads one line of `add` output. Progress updates carry only `Name` and
+/// `Bytes`, and give `None`.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_added(line: &Value) -> Result<Option<AddedObject>, IPFSError> {
+    if line.get("Hash").is_none() {
+        return Ok(None);
+    }
+    Ok(Some(AddedObject {
+        name: string_field(line, "Name")?,
+        hash: string_field(line, "Hash")?.parse()?,
+        size: u64_field(line, "Size")?
+    }))
+}
+
+/// The form that uploads `path`, named after its last component. Files are
+/// only opened as the body reaches them.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn path_form(path: &Path, options: &AddOptions) -> Result<Multipart<'static>, IPFSError> {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => return Err(IPFSError::Io(::std::io::Error::new(::std::io::ErrorKind::InvalidInput,
+                                                               "path has no file name")))
+    };
+    let mut form = Multipart::new();
+    add_tree(&mut form, path, &name, options)?;
+    Ok(form)
+}
+
+/// Queues `path` and, for directories, its children in the form under `name`.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn add_tree(form: &mut Multipart, path: &Path, name: &str, options: &AddOptions) -> Result<(), IPFSError> {
+    let metadata = fs::symlink_metadata(path)?;
+    let local = FileAttrs::from_metadata(&metadata);
+    let attrs = FileAttrs {
+        mode: if options.preserve_mode { local.mode } else { None },
+        mtime: if options.preserve_mtime { local.mtime } else { None }
+    };
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(path)?;
+        form.add_symlink(name, &target.to_string_lossy());
+        form.set_attrs(attrs);
+    } else if metadata.is_dir() {
+        form.add_directory(name);
+        form.set_attrs(attrs);
+        let mut children = Vec::new();
+        for entry in fs::read_dir(path)? {
+            children.push(entry?.path());
+        }
+        children.sort();
+        for child in children {
+            let child_name = format!("{}/{}", name, child.file_name().unwrap().to_string_lossy());
+            add_tree(form, &child, &child_name, options)?;
+        }
+    } else {
+        form.add_file(name, path.to_path_buf());
+        form.set_attrs(attrs);
+    }
+    Ok(())
+}
@@ -0,0 +1,778 @@
+//! `AsyncIPFS`, a non-blocking client for services running on tokio. All of
+//! its requests go through one `reqwest::Client`, whose connections to the
+//! daemon are pooled and kept alive, and responses are decoded with the same
+//! parsers as the blocking client.
+
+use add::{parse_added, path_form, AddOptions, AddedObject};
+use blocks::{self, check_block, check_put, check_removed, BlockPutOptions, BlockStat};
+use bytes::Bytes;
+use cancel::{CancelHandle, Cancellable};
+use cid::Cid;
+use client::{ClientOptions, RetryPolicy};
+use dag::{final_stat, link_field, parse_root, DagPutOptions, DagResolved, DagStat};
+use dagpb::encode_dag_pb;
+use endpoint::Endpoint;
+use error::IPFSError;
+use futures_util::future::{self, Future, FutureExt, TryFutureExt};
+use futures_util::stream::{self, Stream, TryStreamExt};
+use get::GetOptions;
+use ipld::Ipld;
+use key::{gen_args, keys_field, parse_key, parse_rename, removed_key, Key, KeyFormat, KeyRename, KeyType};
+use ls::{parse_objects, Entry, LsOptions};
+use merkledag::PBNode;
+use mfs::{self, cid_field, parse_entries, push_format_args, FileStat, MfsEntry, MkdirOptions, WriteOptions};
+use multipart::Multipart;
+use name::{self, split_ipns, NameEntry, PublishOptions, ResolveOptions};
+use object::{self, decode_object, hash_field, parse_changes, ObjectChange, ObjectLink, ObjectStat, ObjectTemplate};
+use pin::{self as pins, not_pinned, parse_pin, parse_pins, parse_verification, pins_field, PinType,
+          PinVerification};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, RequestBuilder, Response};
+use response::{read_json, string_field};
+use serde_json::{self, Value};
+use std::io::Read;
+use std::mem;
+use std::path::Path;
+use std::pin::Pin;
+use std::str;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time;
+use url::Url;
+
+/// A request in flight, resolving to its decoded response.
+pub type IpfsFuture<T> = Pin<Box<dyn Future<Output = Result<T, IPFSError>> + Send>>;
+
+/// The results of a streaming command, decoded as they arrive.
+pub type IpfsStream<T> = Pin<Box<dyn Stream<Item = Result<T, IPFSError>> + Send>>;
+
+/// The async client. Clones are cheap and share the connection pool, so one
+/// can be handed to every task that talks to the daemon.
+#[derive(Debug, Clone)]
+pub struct AsyncIPFS {
+    endpoint: Endpoint,
+    client: Client,
+    retry: RetryPolicy,
+    timeout: Option<Duration>
+}
+
+impl AsyncIPFS {
+    pub fn new(host: String, port: u16) -> AsyncIPFS {
+        AsyncIPFS::with_endpoint(Endpoint::new(&host, port))
+    }
+
+    /// A client for the API at `endpoint`, given as a multiaddr or URL.
+    pub fn with_endpoint(endpoint: Endpoint) -> AsyncIPFS {
+        AsyncIPFS::with_client(endpoint, Client::new())
+    }
+
+    /// A client for the API at `endpoint` with the given headers,
+    /// credentials, TLS settings, timeouts and retry policy. Retrying needs
+    /// the tokio runtime's timer.
+    pub fn with_options(endpoint: Endpoint, options: &ClientOptions) -> Result<AsyncIPFS, IPFSError> {
+        let mut ipfs = AsyncIPFS::with_client(endpoint, options.async_client()?);
+        ipfs.retry = options.retry_policy().clone();
+        Ok(ipfs)
+    }
+
+    /// Sends requests through `client`, e.g. one the service has already
+    /// configured and shares with other APIs. Nothing is retried.
+    pub fn with_client(endpoint: Endpoint, client: Client) -> AsyncIPFS {
+        AsyncIPFS { endpoint, client, retry: RetryPolicy::new(), timeout: None }
+    }
+
+    pub fn from_multiaddr(addr: &str) -> Result<AsyncIPFS, IPFSError> {
+        Ok(AsyncIPFS::with_endpoint(Endpoint::from_multiaddr(addr)?))
+    }
+
+    pub fn from_url(url: &str) -> Result<AsyncIPFS, IPFSError> {
+        Ok(AsyncIPFS::with_endpoint(Endpoint::from_url(url)?))
+    }
+
+    /// A client for the local daemon, found through the `api` file of
+    /// `$IPFS_PATH` or `~/.ipfs`.
+    pub fn discover() -> Result<AsyncIPFS, IPFSError> {
+        Ok(AsyncIPFS::with_endpoint(Endpoint::discover()?))
+    }
+
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    /// A client sharing this one's connections whose requests must each be
+    /// over, response and all, within `timeout`.
+    pub fn with_timeout(&self, timeout: Duration) -> AsyncIPFS {
+        AsyncIPFS { timeout: Some(timeout), ..self.clone() }
+    }
+
+    /// Sends `cmd` with the given query arguments. The daemon's RPC API only
+    /// accepts POST, even for commands without a body.
+    fn request(&self, cmd: &str, args: &[(&str, String)]) -> IpfsFuture<Response> {
+        self.attempt(cmd.to_string(), self.endpoint.url(cmd, args), 0)
+    }
+
+    /// Sends a request without a body, then again after a pause for as long
+    /// as the retry policy allows.
+    fn attempt(&self, cmd: String, url: Url, attempt: u32) -> IpfsFuture<Response> {
+        let ipfs = self.clone();
+        Box::pin(send(self.post(&url)).or_else(move |err| -> IpfsFuture<Response> {
+            match ipfs.retry.retry_after(&cmd, attempt, &err) {
+                Some(backoff) => Box::pin(time::sleep(backoff).then(move |()| ipfs.attempt(cmd, url, attempt + 1))),
+                None => Box::pin(future::err(err))
+            }
+        }))
+    }
+
+    fn post(&self, url: &Url) -> RequestBuilder {
+        let request = self.client.post(url.as_str());
+        match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request
+        }
+    }
+
+    /// Like `request`, with `form` as a multipart body. The form is read
+    /// into memory first, so this is only used for data the caller already
+    /// holds.
+    fn request_with_body(&self, cmd: &str, args: &[(&str, String)], mut form: Multipart)
+                         -> IpfsFuture<Response> {
+        let mut body = Vec::new();
+        if let Err(err) = form.read_to_end(&mut body) {
+            return Box::pin(future::err(IPFSError::from(err)));
+        }
+        send(self.post(&self.endpoint.url(cmd, args)).header(CONTENT_TYPE, form.content_type()).body(body))
+    }
+
+    /// Sends `cmd` with `path`, after resolving a leading `/ipns/` name, as
+    /// its first argument.
+    fn request_path(&self, cmd: &'static str, path: &str, args: Vec<(&'static str, String)>)
+                    -> IpfsFuture<Response> {
+        let ipfs = self.clone();
+        Box::pin(self.resolve_path(path).and_then(move |path| {
+            let mut all = vec![("arg", path)];
+            all.extend(args);
+            ipfs.request(cmd, &all)
+        }))
+    }
+
+    pub fn cat(&self, path: &str) -> IpfsFuture<String> {
+        Box::pin(self.cat_stream(path, None, None)
+                     .try_fold(Vec::new(), |mut content, chunk| {
+                         content.extend_from_slice(&chunk);
+                         future::ok(content)
+                     })
+                     .and_then(|content| {
+                         future::ready(String::from_utf8(content).map_err(|err| IPFSError::Utf8(err.utf8_error())))
+                     }))
+    }
+
+    /// Streams the contents of the file at `path` as the daemon sends them,
+    /// optionally starting at byte `offset` and stopping after `length`
+    /// bytes.
+    pub fn cat_stream(&self, path: &str, offset: Option<u64>, length: Option<u64>) -> IpfsStream<Bytes> {
+        let mut args = Vec::new();
+        if let Some(offset) = offset {
+            args.push(("offset", offset.to_string()));
+        }
+        if let Some(length) = length {
+            args.push(("length", length.to_string()));
+        }
+        Box::pin(self.request_path("cat", path, args)
+                     .map_ok(|res| res.bytes_stream().map_err(IPFSError::from))
+                     .try_flatten_stream())
+    }
+
+    /// Adds the contents of `data` as a single file called `name`. `data` is
+    /// read into memory when this is called.
+    pub fn add<R: Read>(&self, name: &str, data: R, options: &AddOptions) -> IpfsFuture<Vec<AddedObject>> {
+        let mut form = Multipart::new();
+        form.add_reader(name, data);
+        self.add_form(form, options)
+    }
+
+    /// Adds `data` as a single file called `name`.
+    pub fn add_bytes(&self, name: &str, data: &[u8], options: &AddOptions) -> IpfsFuture<Vec<AddedObject>> {
+        self.add(name, data, options)
+    }
+
+    /// Adds a local file, or a directory and everything below it, as
+    /// `IPFS::add_path` does. The files are read into memory when this is
+    /// called.
+    pub fn add_path(&self, path: &Path, options: &AddOptions) -> IpfsFuture<Vec<AddedObject>> {
+        match path_form(path, options) {
+            Ok(form) => self.add_form(form, options),
+            Err(err) => Box::pin(future::err(err))
+        }
+    }
+
+    fn add_form(&self, form: Multipart, options: &AddOptions) -> IpfsFuture<Vec<AddedObject>> {
+        let mut args = options.to_args();
+        args.push(("progress", "false".to_string()));
+        Box::pin(self.request_with_body("add", &args, form)
+                     .map_ok(json_lines)
+                     .try_flatten_stream()
+                     .try_filter_map(|line| future::ready(parse_added(&line)))
+                     .try_collect())
+    }
+
+    /// Fetches the raw bytes of a block, checked against its CID.
+    pub fn block_get(&self, cid: &Cid) -> IpfsFuture<Vec<u8>> {
+        let cid = cid.clone();
+        Box::pin(self.request("block/get", &[("arg", cid.to_string())])
+                     .and_then(|res| res.bytes().map_err(IPFSError::from))
+                     .and_then(move |block| future::ready(check_block(&cid, &block).map(|()| block.to_vec()))))
+    }
+
+    /// Stores `block`, checking the CID it was filed under as `IPFS::block_put`
+    /// does.
+    pub fn block_put(&self, block: &[u8], options: &BlockPutOptions) -> IpfsFuture<BlockStat> {
+        let mut form = Multipart::new();
+        form.add_reader("data", block);
+        let block = block.to_vec();
+        Box::pin(self.request_with_body("block/put", &options.to_args(), form)
+                     .and_then(json_body)
+                     .and_then(move |json| {
+                         future::ready(blocks::parse_stat(&json).and_then(|stat| check_put(stat, &block)))
+                     }))
+    }
+
+    pub fn block_stat(&self, cid: &Cid) -> IpfsFuture<BlockStat> {
+        parse_json(self.request("block/stat", &[("arg", cid.to_string())]), blocks::parse_stat)
+    }
+
+    /// Removes blocks from the local datastore. Pinned blocks cannot be
+    /// removed; `force` ignores blocks that are not present.
+    pub fn block_rm(&self, cids: &[Cid], force: bool) -> IpfsFuture<()> {
+        let mut args = vec![("force", force.to_string())];
+        for cid in cids {
+            args.push(("arg", cid.to_string()));
+        }
+        Box::pin(parse_lines(self.request("block/rm", &args), check_removed).try_collect())
+    }
+
+    /// Fetches the IPLD node at `path`, whatever codec it is stored with.
+    pub fn dag_get(&self, path: &str) -> IpfsFuture<Ipld> {
+        Box::pin(self.request_path("dag/get", path, vec![("output-codec", "dag-json".to_string())])
+                     .and_then(json_body)
+                     .and_then(|json| future::ready(Ipld::from_dag_json(&json))))
+    }
+
+    /// Stores `value` and returns its CID.
+    pub fn dag_put(&self, value: &Ipld, options: &DagPutOptions) -> IpfsFuture<Cid> {
+        let options = options.clone().input_codec("dag-json");
+        match value.to_dag_json() {
+            Ok(json) => self.dag_put_raw(json.to_string().as_bytes(), &options),
+            Err(err) => Box::pin(future::err(err))
+        }
+    }
+
+    /// Stores a node already encoded in the options' input codec. `data` is
+    /// read into memory when this is called.
+    pub fn dag_put_raw<R: Read>(&self, data: R, options: &DagPutOptions) -> IpfsFuture<Cid> {
+        let mut form = Multipart::new();
+        form.add_reader("object data", data);
+        Box::pin(self.request_with_body("dag/put", &options.to_args(), form)
+                     .and_then(json_body)
+                     .and_then(|json| future::ready(link_field(&json, "Cid"))))
+    }
+
+    pub fn dag_resolve(&self, path: &str) -> IpfsFuture<DagResolved> {
+        Box::pin(self.request_path("dag/resolve", path, Vec::new())
+                     .and_then(json_body)
+                     .and_then(|json| {
+                         future::ready(link_field(&json, "Cid").and_then(|cid| {
+                             Ok(DagResolved { cid, rem_path: string_field(&json, "RemPath")? })
+                         }))
+                     }))
+    }
+
+    /// Walks the whole DAG below `path`, fetching any blocks that are not
+    /// local.
+    pub fn dag_stat(&self, path: &str) -> IpfsFuture<DagStat> {
+        Box::pin(self.request_path("dag/stat", path, vec![("progress", "false".to_string())])
+                     .map_ok(json_lines)
+                     .try_flatten_stream()
+                     .try_fold(None, |_, line| future::ok(Some(line)))
+                     .and_then(|last| future::ready(final_stat(last))))
+    }
+
+    /// Streams the DAG below `cid` as a CAR file.
+    pub fn dag_export(&self, cid: &Cid) -> IpfsStream<Bytes> {
+        body_stream(self.request("dag/export", &[("arg", cid.to_string())]))
+    }
+
+    /// Imports the blocks of a CAR file read from `car`, returning its roots.
+    /// They are pinned recursively if `pin_roots` is set. `car` is read into
+    /// memory when this is called.
+    pub fn dag_import<R: Read>(&self, car: R, pin_roots: bool) -> IpfsFuture<Vec<Cid>> {
+        let mut form = Multipart::new();
+        form.add_reader("file", car);
+        Box::pin(parse_lines(self.request_with_body("dag/import", &[("pin-roots", pin_roots.to_string())], form),
+                             parse_root)
+                     .try_filter_map(future::ok)
+                     .try_collect())
+    }
+
+    /// Streams the file or directory at `path` as a tar archive, compressed
+    /// if the options ask. Unlike `IPFS::get_to_path`, nothing is extracted.
+    pub fn get(&self, path: &str, options: &GetOptions) -> IpfsStream<Bytes> {
+        body_stream(self.request_path("get", path, options.to_args()))
+    }
+
+    /// Lists the directory at `path`. Unlike `IPFS::ls`, this never looks up
+    /// cumulative sizes, whatever `LsOptions::cumulative_sizes` says.
+    pub fn ls(&self, path: &str, options: &LsOptions) -> IpfsFuture<Vec<Entry>> {
+        Box::pin(self.request_path("ls", path, options.to_args())
+                     .and_then(json_body)
+                     .and_then(|json| future::ready(parse_objects(&json))))
+    }
+
+    /// Lists the directory at `path` as the daemon reads it, one entry at a
+    /// time.
+    pub fn ls_stream(&self, path: &str, options: &LsOptions) -> IpfsStream<Entry> {
+        let mut args = vec![("stream", "true".to_string())];
+        args.extend(options.to_args());
+        Box::pin(self.request_path("ls", path, args)
+                     .map_ok(json_lines)
+                     .try_flatten_stream()
+                     .and_then(|json| future::ready(parse_objects(&json)))
+                     .map_ok(|entries| stream::iter(entries.into_iter().map(Ok)))
+                     .try_flatten())
+    }
+
+    /// Publishes `path` (e.g. `/ipfs/Qm...`) under an IPNS name.
+    pub fn name_publish(&self, path: &str, options: &PublishOptions) -> IpfsFuture<NameEntry> {
+        let mut args = options.to_args();
+        args.push(("arg", path.to_string()));
+        parse_json(self.request("name/publish", &args), name::parse_entry)
+    }
+
+    /// Resolves an IPNS name to the path it points at.
+    pub fn name_resolve(&self, name: &str, options: &ResolveOptions) -> IpfsFuture<String> {
+        let mut args = vec![("arg", name.to_string())];
+        args.extend(options.to_args());
+        Box::pin(self.request("name/resolve", &args)
+                     .and_then(json_body)
+                     .and_then(|json| future::ready(string_field(&json, "Path"))))
+    }
+
+    /// Pins `path`, and everything below it if `recursive`. Returns the CIDs
+    /// that were pinned.
+    pub fn pin_add(&self, path: &str, recursive: bool) -> IpfsFuture<Vec<Cid>> {
+        parse_json(self.request("pin/add", &[("arg", path.to_string()), ("recursive", recursive.to_string())]),
+                   pins_field)
+    }
+
+    /// Removes the pin on `path`. Fails with `IPFSError::NotPinned` if there
+    /// is no pin of the given kind.
+    pub fn pin_rm(&self, path: &str, recursive: bool) -> IpfsFuture<Vec<Cid>> {
+        let res = self.request("pin/rm", &[("arg", path.to_string()), ("recursive", recursive.to_string())]);
+        parse_json(not_pinned_for(res, path), pins_field)
+    }
+
+    /// Lists pins of the given kind, or checks a single `path` if one is
+    /// given.
+    pub fn pin_ls(&self, path: Option<&str>, filter: PinType) -> IpfsFuture<Vec<pins::Pin>> {
+        let mut args = vec![("type", filter.as_str().to_string())];
+        if let Some(path) = path {
+            args.push(("arg", path.to_string()));
+        }
+        parse_json(not_pinned_for(self.request("pin/ls", &args), path.unwrap_or("")), parse_pins)
+    }
+
+    /// Lists pins of the given kind as the daemon finds them.
+    pub fn pin_ls_stream(&self, filter: PinType) -> IpfsStream<pins::Pin> {
+        parse_lines(self.request("pin/ls", &[("type", filter.as_str().to_string()), ("stream", "true".to_string())]),
+                    parse_pin)
+    }
+
+    /// Moves a recursive pin from `from` to `to`, keeping the old one unless
+    /// `unpin`.
+    pub fn pin_update(&self, from: &str, to: &str, unpin: bool) -> IpfsFuture<Vec<Cid>> {
+        let res = self.request("pin/update", &[("arg", from.to_string()), ("arg", to.to_string()),
+                                               ("unpin", unpin.to_string())]);
+        parse_json(not_pinned_for(res, from), pins_field)
+    }
+
+    /// Checks that every recursively pinned tree is complete and intact. With
+    /// `verbose` false the daemon only reports the pins that failed.
+    pub fn pin_verify(&self, verbose: bool) -> IpfsStream<PinVerification> {
+        parse_lines(self.request("pin/verify", &[("verbose", verbose.to_string())]), parse_verification)
+    }
+
+    pub fn key_gen(&self, name: &str, kind: KeyType) -> IpfsFuture<Key> {
+        parse_json(self.request("key/gen", &gen_args(name, kind)), parse_key)
+    }
+
+    pub fn key_list(&self) -> IpfsFuture<Vec<Key>> {
+        parse_json(self.request("key/list", &[("l", "true".to_string())]), keys_field)
+    }
+
+    /// Renames key `old` to `new`, replacing any existing `new` if `force`.
+    pub fn key_rename(&self, old: &str, new: &str, force: bool) -> IpfsFuture<KeyRename> {
+        let args = [("arg", old.to_string()), ("arg", new.to_string()), ("force", force.to_string())];
+        parse_json(self.request("key/rename", &args), parse_rename)
+    }
+
+    /// Removes the named key and returns it.
+    pub fn key_rm(&self, name: &str) -> IpfsFuture<Key> {
+        parse_json(self.request("key/rm", &[("arg", name.to_string())]), removed_key)
+    }
+
+    /// Imports a private key read from `key` under `name`.
+    pub fn key_import<R: Read>(&self, name: &str, key: R, format: KeyFormat) -> IpfsFuture<Key> {
+        let mut form = Multipart::new();
+        form.add_reader("key", key);
+        let args = [("arg", name.to_string()), ("format", format.as_str().to_string())];
+        parse_json(self.request_with_body("key/import", &args, form), parse_key)
+    }
+
+    /// Exports the named private key.
+    pub fn key_export(&self, name: &str, format: KeyFormat) -> IpfsFuture<Vec<u8>> {
+        let args = [("arg", name.to_string()), ("format", format.as_str().to_string())];
+        Box::pin(self.request("key/export", &args)
+                     .and_then(|res| res.bytes().map_err(IPFSError::from))
+                     .map_ok(|key| key.to_vec()))
+    }
+
+    /// Fetches the dag-pb node at `path`. When the path names a block
+    /// directly, its bytes are checked against the CID before decoding.
+    pub fn object_get(&self, path: &str) -> IpfsFuture<PBNode> {
+        let ipfs = self.clone();
+        Box::pin(self.resolve_path(path).and_then(move |path| {
+            ipfs.request("object/get", &[("arg", path.clone()), ("encoding", "protobuf".to_string())])
+                .and_then(|res| res.bytes().map_err(IPFSError::from))
+                .and_then(move |block| future::ready(decode_object(&path, &block)))
+        }))
+    }
+
+    pub fn object_new(&self, template: ObjectTemplate) -> IpfsFuture<Cid> {
+        parse_json(self.request("object/new", &template.to_args()), hash_field)
+    }
+
+    /// Stores `node` in canonical form and returns its CID, which is checked
+    /// against the bytes that were sent.
+    pub fn object_put(&self, node: &PBNode) -> IpfsFuture<Cid> {
+        let block = match encode_dag_pb(node) {
+            Ok(block) => block,
+            Err(err) => return Box::pin(future::err(err))
+        };
+        let mut form = Multipart::new();
+        form.add_reader("node", &block[..]);
+        let res = self.request_with_body("object/put", &[("inputenc", "protobuf".to_string())], form);
+        Box::pin(parse_json(res, hash_field)
+                     .and_then(move |cid| future::ready(cid.verify(&block).map(|()| cid))))
+    }
+
+    pub fn object_stat(&self, path: &str) -> IpfsFuture<ObjectStat> {
+        parse_json(self.request_path("object/stat", path, Vec::new()), object::parse_stat)
+    }
+
+    pub fn object_links(&self, path: &str) -> IpfsFuture<Vec<ObjectLink>> {
+        parse_json(self.request_path("object/links", path, Vec::new()), object::parse_links)
+    }
+
+    /// Lists the changes that turn the tree at `from` into the one at `to`.
+    pub fn object_diff(&self, from: &str, to: &str) -> IpfsFuture<Vec<ObjectChange>> {
+        let ipfs = self.clone();
+        let to = self.resolve_path(to);
+        Box::pin(self.resolve_path(from)
+                     .and_then(move |from| to.map_ok(move |to| vec![("arg", from), ("arg", to)]))
+                     .and_then(move |args| parse_json(ipfs.request("object/diff", &args), parse_changes)))
+    }
+
+    /// Adds a link called `name` to `target` under the node at `root`,
+    /// creating intermediate nodes for a `/`-separated name if `create`.
+    /// Returns the CID of the new root.
+    pub fn object_patch_add_link(&self, root: &str, name: &str, target: &Cid, create: bool) -> IpfsFuture<Cid> {
+        let args = vec![("arg", name.to_string()), ("arg", target.to_string()), ("create", create.to_string())];
+        parse_json(self.request_path("object/patch/add-link", root, args), hash_field)
+    }
+
+    /// Removes the link called `name` from the node at `root`. Returns the
+    /// CID of the new root.
+    pub fn object_patch_rm_link(&self, root: &str, name: &str) -> IpfsFuture<Cid> {
+        parse_json(self.request_path("object/patch/rm-link", root, vec![("arg", name.to_string())]), hash_field)
+    }
+
+    /// Appends the contents of `data`, read into memory when this is called,
+    /// to the node's data field.
+    pub fn object_patch_append_data<R: Read>(&self, root: &str, data: R) -> IpfsFuture<Cid> {
+        self.patch_data("object/patch/append-data", root, data)
+    }
+
+    /// Replaces the node's data field with the contents of `data`, read into
+    /// memory when this is called.
+    pub fn object_patch_set_data<R: Read>(&self, root: &str, data: R) -> IpfsFuture<Cid> {
+        self.patch_data("object/patch/set-data", root, data)
+    }
+
+    fn patch_data<R: Read>(&self, cmd: &'static str, root: &str, data: R) -> IpfsFuture<Cid> {
+        let mut body = Vec::new();
+        if let Err(err) = { data }.read_to_end(&mut body) {
+            return Box::pin(future::err(IPFSError::from(err)));
+        }
+        let ipfs = self.clone();
+        Box::pin(self.resolve_path(root).and_then(move |root| {
+            let mut form = Multipart::new();
+            form.add_reader("data", &body[..]);
+            parse_json(ipfs.request_with_body(cmd, &[("arg", root)], form), hash_field)
+        }))
+    }
+
+    /// The node's MFS, as `IPFS::files` gives for the blocking client.
+    pub fn files(&self) -> AsyncMfs {
+        AsyncMfs { ipfs: self.clone() }
+    }
+
+    /// Resolves a leading `/ipns/` name, as `IPFS::resolve_path` does. Other
+    /// paths are returned as is.
+    pub fn resolve_path(&self, path: &str) -> IpfsFuture<String> {
+        let (name, subpath) = match split_ipns(path) {
+            Some(split) => split,
+            None => return Box::pin(future::ok(path.to_string()))
+        };
+        let subpath = subpath.to_string();
+        Box::pin(self.name_resolve(name, &ResolveOptions::new())
+                     .map_ok(move |resolved| format!("{}{}", resolved, subpath)))
+    }
+}
+
+/// A handle on the node's MFS, obtained with `AsyncIPFS::files`. Paths are
+/// absolute MFS paths such as `/site/index.html`.
+#[derive(Debug, Clone)]
+pub struct AsyncMfs {
+    ipfs: AsyncIPFS
+}
+
+impl AsyncMfs {
+    pub fn mkdir(&self, path: &str, options: &MkdirOptions) -> IpfsFuture<()> {
+        let mut args = options.to_args();
+        args.push(("arg", path.to_string()));
+        done(self.ipfs.request("files/mkdir", &args))
+    }
+
+    /// Writes the contents of `data` into the file at `path`. `data` is read
+    /// into memory when this is called.
+    pub fn write<R: Read>(&self, path: &str, data: R, options: &WriteOptions) -> IpfsFuture<()> {
+        let mut args = options.to_args();
+        args.push(("arg", path.to_string()));
+        let mut form = Multipart::new();
+        form.add_reader("data", data);
+        done(self.ipfs.request_with_body("files/write", &args, form))
+    }
+
+    /// Streams the file at `path`, optionally starting at `offset` and
+    /// stopping after `count` bytes.
+    pub fn read(&self, path: &str, offset: Option<u64>, count: Option<u64>) -> IpfsStream<Bytes> {
+        let mut args = vec![("arg", path.to_string())];
+        if let Some(offset) = offset {
+            args.push(("offset", offset.to_string()));
+        }
+        if let Some(count) = count {
+            args.push(("count", count.to_string()));
+        }
+        body_stream(self.ipfs.request("files/read", &args))
+    }
+
+    pub fn mv(&self, from: &str, to: &str) -> IpfsFuture<()> {
+        done(self.ipfs.request("files/mv", &[("arg", from.to_string()), ("arg", to.to_string())]))
+    }
+
+    /// Copies `from`, which may be an MFS path or an `/ipfs/` path, to `to`.
+    pub fn cp(&self, from: &str, to: &str, parents: bool) -> IpfsFuture<()> {
+        let args = [("arg", from.to_string()), ("arg", to.to_string()), ("parents", parents.to_string())];
+        done(self.ipfs.request("files/cp", &args))
+    }
+
+    /// Removes `path`. Directories need `recursive`; `force` also ignores
+    /// paths that do not exist.
+    pub fn rm(&self, path: &str, recursive: bool, force: bool) -> IpfsFuture<()> {
+        let args = [("arg", path.to_string()), ("recursive", recursive.to_string()), ("force", force.to_string())];
+        done(self.ipfs.request("files/rm", &args))
+    }
+
+    pub fn stat(&self, path: &str) -> IpfsFuture<FileStat> {
+        parse_json(self.ipfs.request("files/stat", &[("arg", path.to_string())]), mfs::parse_stat)
+    }
+
+    pub fn ls(&self, path: &str) -> IpfsFuture<Vec<MfsEntry>> {
+        let args = [("arg", path.to_string()), ("long", "true".to_string())];
+        parse_json(self.ipfs.request("files/ls", &args), parse_entries)
+    }
+
+    /// Writes any changes below `path` out to the blockstore and returns the
+    /// CID of `path`.
+    pub fn flush(&self, path: &str) -> IpfsFuture<Cid> {
+        parse_json(self.ipfs.request("files/flush", &[("arg", path.to_string())]), cid_field)
+    }
+
+    /// Re-encodes `path` with a different CID version or hash function.
+    pub fn chcid(&self, path: &str, cid_version: Option<u32>, hash: Option<&str>) -> IpfsFuture<()> {
+        let mut args = vec![("arg", path.to_string())];
+        push_format_args(&mut args, cid_version, &hash.map(|h| h.to_string()));
+        done(self.ipfs.request("files/chcid", &args))
+    }
+}
+
+/// Makes `stream` stoppable through the returned handle, e.g. to abandon a
+/// `cat_stream` from a task other than the one reading it. The stream ends
+/// with `IPFSError::Cancelled` as soon as the handle is cancelled.
+pub fn cancellable<T: 'static>(stream: IpfsStream<T>) -> (IpfsStream<T>, CancelHandle) {
+    let cancel = CancelHandle::new();
+    (Box::pin(Cancellable::new(stream, cancel.clone())), cancel)
+}
+
+fn send(request: RequestBuilder) -> IpfsFuture<Response> {
+    Box::pin(request.send().map_err(IPFSError::from).and_then(check_status))
+}
+
+/// Passes successful responses on, and turns error statuses into an
+/// `IPFSError` carrying the daemon's error body.
+fn check_status(res: Response) -> IpfsFuture<Response> {
+    if res.status().is_success() {
+        return Box::pin(future::ok(res));
+    }
+    let status = res.status();
+    Box::pin(res.text().then(move |body| {
+        future::err(match body {
+            Ok(body) => IPFSError::from_response(status, body),
+            Err(err) => IPFSError::from(err)
+        })
+    }))
+}
+
+/// Waits for a command whose response carries nothing of interest.
+fn done(res: IpfsFuture<Response>) -> IpfsFuture<()> {
+    Box::pin(res.map_ok(|_| ()))
+}
+
+/// Turns the daemon's "not pinned" errors for `path` into
+/// `IPFSError::NotPinned`.
+fn not_pinned_for(res: IpfsFuture<Response>, path: &str) -> IpfsFuture<Response> {
+    let path = path.to_string();
+    Box::pin(res.map_err(move |err| not_pinned(err, &path)))
+}
+
+fn body_stream(res: IpfsFuture<Response>) -> IpfsStream<Bytes> {
+    Box::pin(res.map_ok(|res| res.bytes_stream().map_err(IPFSError::from)).try_flatten_stream())
+}
+
+/// Decodes a whole JSON response body with `parse`, one of the parsers the
+/// blocking client uses.
+fn parse_json<T: Send + 'static>(res: IpfsFuture<Response>, parse: fn(&Value) -> Result<T, IPFSError>)
+                                 -> IpfsFuture<T> {
+    Box::pin(res.and_then(json_body).and_then(move |json| future::ready(parse(&json))))
+}
+
+/// Decodes each line of a newline-delimited JSON response with `parse`.
+fn parse_lines<T: Send + 'static>(res: IpfsFuture<Response>, parse: fn(&Value) -> Result<T, IPFSError>)
+                                  -> IpfsStream<T> {
+    Box::pin(res.map_ok(json_lines).try_flatten_stream().and_then(move |json| future::ready(parse(&json))))
+}
+
+/// Reads a whole response body as a single JSON value.
+fn json_body(res: Response) -> IpfsFuture<Value> {
+    Box::pin(res.bytes().map_err(IPFSError::from).and_then(|body| future::ready(read_json(&body[..]))))
+}
+
+fn json_lines(res: Response) -> IpfsStream<Value> {
+    Box::pin(JsonLines::new(Box::pin(res.bytes_stream().map_err(IPFSError::from))))
+}
+
+/// Splits a newline-delimited JSON body into values as its chunks arrive,
+/// which need not line up with the lines.
+struct JsonLines {
+    body: IpfsStream<Bytes>,
+    buffer: Vec<u8>,
+    finished: bool
+}
+
+impl JsonLines {
+    fn new(body: IpfsStream<Bytes>) -> JsonLines {
+        JsonLines { body, buffer: Vec::new(), finished: false }
+    }
+}
+
+impl Stream for JsonLines {
+    type Item = Result<Value, IPFSError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Result<Value, IPFSError>>> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..end + 1).collect();
+                match parse_line(&line) {
+                    Some(json) => return Poll::Ready(Some(json)),
+                    None => continue
+                }
+            }
+            if self.finished {
+                let rest = mem::take(&mut self.buffer);
+                return Poll::Ready(parse_line(&rest));
+            }
+            match self.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => self.buffer.extend_from_slice(&chunk),
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => self.finished = true,
+                Poll::Pending => return Poll::Pending
+            }
+        }
+    }
+}
+
+/// Parses one line of a body, giving `None` for a blank one.
+fn parse_line(line: &[u8]) -> Option<Result<Value, IPFSError>> {
+    let line = match str::from_utf8(line) {
+        Ok(line) => line,
+        Err(err) => return Some(Err(IPFSError::from(err)))
+    };
+    if line.trim().is_empty() {
+        return None;
+    }
+    Some(serde_json::from_str::<Value>(line).map_err(IPFSError::from))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use futures_executor::block_on;
+    use http;
+    use http::StatusCode;
+
+    #[test]
+    fn lines_are_split_across_chunks() {
+        let chunks = vec![Ok(Bytes::from_static(b"{\"Name\":\"a\"}\n{\"Na")),
+                          Ok(Bytes::from_static(b"me\":\"b\"}\n\n{\"Name\":\"c\"}"))];
+        let lines: Vec<Value> = block_on(JsonLines::new(Box::pin(stream::iter(chunks))).try_collect()).unwrap();
+        let names: Vec<&str> = lines.iter().map(|line| line.get("Name").unwrap().as_str().unwrap()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn error_statuses_carry_the_daemon_message() {
+        let res = http::Response::builder().status(500)
+                                           .body(r#"{"Message":"invalid path","Code":0,"Type":"error"}"#)
+                                           .unwrap();
+        match block_on(check_status(Response::from(res))) {
+            Err(IPFSError::Api(err)) => {
+                assert_eq!(err.status, StatusCode::INTERNAL_SERVER_ERROR);
+                assert_eq!(err.message, "invalid path");
+            }
+            other => panic!("unexpected result {:?}", other.map(|res| res.status()))
+        }
+    }
+
+    #[test]
+    fn streamed_lines_use_the_shared_parsers() {
+        let body = "{\"Cid\":\"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt\",\"Type\":\"recursive\"}\n\
+                    {\"Cid\":\"bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq\",\"Type\":\"direct\"}\n";
+        let res = Response::from(http::Response::builder().body(body).unwrap());
+        let pins: Vec<pins::Pin> = block_on(parse_lines(Box::pin(future::ok(res)), parse_pin).try_collect()).unwrap();
+        let kinds: Vec<PinType> = pins.iter().map(|pin| pin.kind).collect();
+        assert_eq!(kinds, vec![PinType::Recursive, PinType::Direct]);
+
+        let res = Response::from(http::Response::builder().body("{\"Hash\":\"QmX\",\"Error\":\"pinned\"}\n").unwrap());
+        assert!(block_on(parse_lines(Box::pin(future::ok(res)), check_removed).try_collect::<()>()).is_err());
+    }
+}
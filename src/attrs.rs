@@ -0,0 +1,295 @@
+//! UnixFS 1.5 file metadata: POSIX permission bits and a modification time,
+//! carried in the optional `mode` and `mtime` fields of a node's `Data`.
+
+use error::IPFSError;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+use std::fs::{self, File, Metadata};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use unixfs::{Data, UnixTime};
+
+/// The permission bits, plus setuid, setgid and sticky. UnixFS ignores the
+/// file type bits of a mode.
+const MODE_MASK: u32 = 0o7777;
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// A modification time as seconds and nanoseconds relative to the Unix epoch.
+/// Times before the epoch have negative `seconds`; `nanos` always counts
+/// forwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mtime {
+    pub seconds: i64,
+    pub nanos: u32
+}
+
+impl Mtime {
+    pub fn new(seconds: i64, nanos: u32) -> Mtime {
+        Mtime { seconds: seconds + (nanos / NANOS_PER_SEC) as i64, nanos: nanos % NANOS_PER_SEC }
+    }
+
+    pub fn from_system_time(time: SystemTime) -> Mtime {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => Mtime::new(after.as_secs() as i64, after.subsec_nanos()),
+            Err(err) => {
+                let before = err.duration();
+                match before.subsec_nanos() {
+                    0 => Mtime::new(-(before.as_secs() as i64), 0),
+                    nanos => Mtime::new(-(before.as_secs() as i64) - 1, NANOS_PER_SEC - nanos)
+                }
+            }
+        }
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        let nanos = Duration::new(0, self.nanos);
+        if self.seconds >= 0 {
+            UNIX_EPOCH + Duration::from_secs(self.seconds as u64) + nanos
+        } else {
+            UNIX_EPOCH - Duration::from_secs(self.seconds.unsigned_abs()) + nanos
+        }
+    }
+}
+
+/// The optional metadata of a file, directory or symlink. Nodes without a
+/// `mode` are read as 0644, or 0755 for directories, by convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FileAttrs {
+    pub mode: Option<u32>,
+    pub mtime: Option<Mtime>
+}
+
+impl FileAttrs {
+    /// The mode and modification time of a local file, as `ipfs add
+    /// --preserve-mode --preserve-mtime` records them. Modes are only
+    /// available on Unix.
+    pub fn from_metadata(metadata: &Metadata) -> FileAttrs {
+        FileAttrs { mode: local_mode(metadata), mtime: metadata.modified().ok().map(Mtime::from_system_time) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mode.is_none() && self.mtime.is_none()
+    }
+
+    /// Restores the attributes on the local file or directory at `path`.
+    /// Symlinks are left alone, since neither can be set on a link itself
+    /// portably.
+    pub fn apply(&self, path: &Path) -> io::Result<()> {
+        if fs::symlink_metadata(path)?.file_type().is_symlink() {
+            return Ok(());
+        }
+        // The time goes first, as the new mode may not let us open the file.
+        if let Some(mtime) = self.mtime {
+            File::open(path)?.set_modified(mtime.to_system_time())?;
+        }
+        if let Some(mode) = self.mode {
+            set_local_mode(path, mode)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn local_mode(metadata: &Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & MODE_MASK)
+}
+
+#[cfg(not(unix))]
+fn local_mode(_: &Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_local_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & MODE_MASK))
+}
+
+#[cfg(not(unix))]
+fn set_local_mode(path: &Path, mode: u32) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    fs::set_permissions(path, permissions)
+}
+
+/// Reads the attributes recorded in a node. The spec leaves out
+/// `FractionalNanoseconds` rather than storing zero, and never allows a
+/// whole second or more.
+pub fn decode(content: &Data) -> Result<FileAttrs, IPFSError> {
+    let mode = if content.has_mode() { Some(content.get_mode() & MODE_MASK) } else { None };
+    let mtime = if content.has_mtime() {
+        let time = content.get_mtime();
+        let nanos = time.get_FractionalNanoseconds();
+        if time.has_FractionalNanoseconds() && (nanos == 0 || nanos >= NANOS_PER_SEC) {
+            return Err(IPFSError::InvalidBlock(format!("mtime has {} fractional nanoseconds", nanos)));
+        }
+        Some(Mtime { seconds: time.get_Seconds(), nanos })
+    } else {
+        None
+    };
+    Ok(FileAttrs { mode, mtime })
+}
+
+/// Records `attrs` in a node, leaving unset fields out.
+pub fn encode(attrs: &FileAttrs, content: &mut Data) {
+    if let Some(mode) = attrs.mode {
+        content.set_mode(mode & MODE_MASK);
+    }
+    if let Some(mtime) = attrs.mtime {
+        let mut time = UnixTime::new();
+        time.set_Seconds(mtime.seconds);
+        if mtime.nanos != 0 {
+            time.set_FractionalNanoseconds(mtime.nanos);
+        }
+        content.set_mtime(time);
+    }
+}
+
+/// Reads `Mode`, and `Mtime` and `MtimeNsecs` or an RFC 3339 `ModTime`, from
+/// a daemon response. Modes come either as an octal string or as a Go
+/// `os.FileMode`, whose setuid, setgid and sticky bits are not where POSIX
+/// puts them.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn from_json(json: &Value) -> FileAttrs {
+    let mode = match json.get("Mode") {
+        Some(Value::String(octal)) => u32::from_str_radix(octal, 8).ok(),
+        Some(mode) => mode.as_u64().map(|mode| go_file_mode(mode as u32)),
+        None => None
+    };
+    let mtime = json.get("Mtime").and_then(|seconds| seconds.as_i64()).map(|seconds| {
+        let nanos = json.get("MtimeNsecs").and_then(|nanos| nanos.as_u64()).unwrap_or(0);
+        Mtime::new(seconds, nanos as u32)
+    }).or_else(|| json.get("ModTime").and_then(|time| time.as_str()).and_then(parse_rfc3339));
+    FileAttrs { mode: mode.map(|mode| mode & MODE_MASK).filter(|&mode| mode != 0), mtime }
+}
+
+/// Parses the `2006-01-02T15:04:05.999999999Z07:00` timestamps Go writes.
+/// Go's zero time, which it writes for a time that was never set, is read
+/// as no time at all.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn parse_rfc3339(time: &str) -> Option<Mtime> {
+    let number = |from: usize, to: usize| time.get(from..to).and_then(number_of);
+    let bytes = time.as_bytes();
+    if bytes.len() < 20 || bytes[4] != b'-' || bytes[7] != b'-' || bytes[10] != b'T' || bytes[13] != b':' ||
+       bytes[16] != b':' {
+        return None;
+    }
+    let (year, month, day) = (number(0, 4)?, number(5, 7)?, number(8, 10)?);
+    if year == 1 && month == 1 && day == 1 {
+        return None;
+    }
+    let seconds = days_from_civil(year, month, day) * 86400 + number(11, 13)? * 3600 + number(14, 16)? * 60 +
+                  number(17, 19)?;
+
+    let mut rest = &time[19..];
+    let mut nanos = 0;
+    if rest.starts_with('.') {
+        let digits = rest[1..].find(|c: char| !c.is_ascii_digit()).map_or(rest.len(), |end| end + 1);
+        let fraction = &rest[1..digits];
+        if fraction.is_empty() || fraction.len() > 9 {
+            return None;
+        }
+        nanos = fraction.parse::<u32>().ok()? * 10u32.pow(9 - fraction.len() as u32);
+        rest = &rest[digits..];
+    }
+    let offset = match rest {
+        "Z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let minutes = number_of(&rest[1..3])? * 60 + number_of(&rest[4..6])?;
+            match rest.as_bytes()[0] {
+                b'+' => minutes * 60,
+                b'-' => -minutes * 60,
+                _ => return None
+            }
+        }
+        _ => return None
+    };
+    Some(Mtime::new(seconds - offset, nanos))
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn number_of(digits: &str) -> Option<i64> {
+    digits.parse().ok()
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn go_file_mode(mode: u32) -> u32 {
+    const GO_SETUID: u32 = 1 << 23;
+    const GO_SETGID: u32 = 1 << 22;
+    const GO_STICKY: u32 = 1 << 20;
+    let mut posix = mode & 0o777;
+    if mode & GO_SETUID != 0 {
+        posix |= 0o4000;
+    }
+    if mode & GO_SETGID != 0 {
+        posix |= 0o2000;
+    }
+    if mode & GO_STICKY != 0 {
+        posix |= 0o1000;
+    }
+    posix
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    use serde_json;
+    use protobuf::Message;
+    use unixfs::Data_DataType;
+
+    #[test]
+    fn times_before_the_epoch_round_trip() {
+        let time = UNIX_EPOCH - Duration::new(1, 250_000_000);
+        let mtime = Mtime::from_system_time(time);
+        assert_eq!(mtime, Mtime { seconds: -2, nanos: 750_000_000 });
+        assert_eq!(mtime.to_system_time(), time);
+    }
+
+    #[test]
+    fn attrs_round_trip_through_a_node() {
+        let attrs = FileAttrs { mode: Some(0o4755), mtime: Some(Mtime::new(1_700_000_000, 0)) };
+        let mut content = Data::new();
+        content.set_Type(Data_DataType::File);
+        encode(&attrs, &mut content);
+        let bytes = content.write_to_bytes().unwrap();
+
+        let mut decoded = Data::new();
+        decoded.merge_from_bytes(&bytes).unwrap();
+        assert!(!decoded.get_mtime().has_FractionalNanoseconds());
+        assert_eq!(decode(&decoded).unwrap(), attrs);
+    }
+
+    #[test]
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn go_modes_are_converted() {
+        let json = serde_json::from_str::<Value>(r#"{"Mode":2147484141,"Mtime":1700000000,"MtimeNsecs":5}"#).unwrap();
+        assert_eq!(from_json(&json), FileAttrs { mode: Some(0o755), mtime: Some(Mtime::new(1_700_000_000, 5)) });
+        let json = serde_json::from_str::<Value>(r#"{"Mode":"0644"}"#).unwrap();
+        assert_eq!(from_json(&json).mode, Some(0o644));
+    }
+
+    #[test]
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn go_timestamps_are_parsed() {
+        assert_eq!(parse_rfc3339("2023-11-14T22:13:20Z"), Some(Mtime::new(1_700_000_000, 0)));
+        assert_eq!(parse_rfc3339("2023-11-15T00:13:20.5+02:00"), Some(Mtime::new(1_700_000_000, 500_000_000)));
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z"), Some(Mtime::new(-1, 0)));
+        assert_eq!(parse_rfc3339("0001-01-01T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("yesterday"), None);
+    }
+}
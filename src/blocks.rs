@@ -0,0 +1,214 @@
+use cid::Cid;
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use multipart::Multipart;
+#[cfg(feature = "blocking")]
+use response::{read_json, JsonLines};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::{string_field, u64_field};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+use std::collections::HashMap;
+#[cfg(feature = "blocking")]
+use std::io::Read;
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// Options for `block_put`. Anything left unset uses the daemon's default,
+/// which is a raw block hashed with sha2-256.
+#[derive(Debug, Clone, Default)]
+pub struct BlockPutOptions {
+    format: Option<String>,
+    mhtype: Option<String>,
+    mhlen: Option<i32>,
+    pin: Option<bool>
+}
+
+impl BlockPutOptions {
+    pub fn new() -> BlockPutOptions {
+        BlockPutOptions::default()
+    }
+
+    /// Multicodec the block is stored under, e.g. `raw`, `dag-pb` or
+    /// `dag-cbor`.
+    pub fn format(mut self, format: &str) -> BlockPutOptions {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    /// Multihash function name, e.g. `sha2-256` or `blake2b-256`.
+    pub fn mhtype(mut self, mhtype: &str) -> BlockPutOptions {
+        self.mhtype = Some(mhtype.to_string());
+        self
+    }
+
+    /// Digest length in bytes; -1 is the function's full length.
+    pub fn mhlen(mut self, mhlen: i32) -> BlockPutOptions {
+        self.mhlen = Some(mhlen);
+        self
+    }
+
+    pub fn pin(mut self, pin: bool) -> BlockPutOptions {
+        self.pin = Some(pin);
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = Vec::new();
+        if let Some(ref format) = self.format {
+            args.push(("format", format.clone()));
+        }
+        if let Some(ref mhtype) = self.mhtype {
+            args.push(("mhtype", mhtype.clone()));
+        }
+        if let Some(mhlen) = self.mhlen {
+            args.push(("mhlen", mhlen.to_string()));
+        }
+        if let Some(pin) = self.pin {
+            args.push(("pin", pin.to_string()));
+        }
+        args
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockStat {
+    pub cid: Cid,
+    pub size: u64
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Fetches the raw bytes of a block, checked against its CID.
+    pub fn block_get(&self, cid: &Cid) -> Result<Vec<u8>, IPFSError> {
+        self.get_block(cid)
+    }
+
+    /// Stores `block` and returns the CID the daemon filed it under. When the
+    /// hash function is one this crate knows, the CID is checked against the
+    /// bytes that were sent.
+    pub fn block_put(&self, block: &[u8], options: &BlockPutOptions) -> Result<BlockStat, IPFSError> {
+        let mut form = Multipart::new();
+        form.add_reader("data", block);
+        let stat = parse_stat(&read_json(self.request_with_body("block/put", &options.to_args(), &mut form)?)?)?;
+        check_put(stat, block)
+    }
+
+    pub fn block_stat(&self, cid: &Cid) -> Result<BlockStat, IPFSError> {
+        parse_stat(&read_json(self.request("block/stat", &[("arg", cid.to_string())])?)?)
+    }
+
+    /// Removes blocks from the local datastore. Pinned blocks cannot be
+    /// removed; `force` ignores blocks that are not present.
+    pub fn block_rm(&self, cids: &[Cid], force: bool) -> Result<(), IPFSError> {
+        let mut args = vec![("force", force.to_string())];
+        for cid in cids {
+            args.push(("arg", cid.to_string()));
+        }
+        for line in JsonLines::new(self.request("block/rm", &args)?) {
+            check_removed(&line?)?;
+        }
+        Ok(())
+    }
+}
+
+/// `block/rm` answers with one line per block, with `Error` set for each that
+/// was not removed.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn check_removed(line: &Value) -> Result<(), IPFSError> {
+    match line.get("Error").and_then(|e| e.as_str()).filter(|e| !e.is_empty()) {
+        Some(error) => Err(IPFSError::UnexpectedResponse(format!("{}: {}", string_field(line, "Hash")?, error))),
+        None => Ok(())
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_stat(json: &Value) -> Result<BlockStat, IPFSError> {
+    Ok(BlockStat { cid: string_field(json, "Key")?.parse()?, size: u64_field(json, "Size")? })
+}
+
+/// Checks the CID a block was stored under against the block, as
+/// `check_block` does.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn check_put(stat: BlockStat, block: &[u8]) -> Result<BlockStat, IPFSError> {
+    check_block(&stat.cid, block)?;
+    Ok(stat)
+}
+
+/// Checks `block` against `cid`, unless the CID uses a hash function this
+/// crate cannot compute, such as blake3. Those blocks are accepted as the
+/// daemon gives them, so that any block that can be put can be read back.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn check_block(cid: &Cid, block: &[u8]) -> Result<(), IPFSError> {
+    match cid.verify(block) {
+        Ok(()) | Err(IPFSError::UnsupportedHash(_)) => Ok(()),
+        Err(err) => Err(err)
+    }
+}
+
+/// Somewhere raw blocks can be fetched from by CID.
+pub trait BlockSource {
+    fn get_block(&self, cid: &Cid) -> Result<Vec<u8>, IPFSError>;
+}
+
+/// An in-memory block store, e.g. the blocks of a CAR file or an import that
+/// has not been sent to a daemon.
+impl BlockSource for HashMap<Cid, Vec<u8>> {
+    fn get_block(&self, cid: &Cid) -> Result<Vec<u8>, IPFSError> {
+        match self.get(cid) {
+            Some(block) => Ok(block.clone()),
+            None => Err(IPFSError::NoSuchHash)
+        }
+    }
+}
+
+impl<S: BlockSource + ?Sized> BlockSource for &S {
+    fn get_block(&self, cid: &Cid) -> Result<Vec<u8>, IPFSError> {
+        (**self).get_block(cid)
+    }
+}
+
+/// Blocks fetched from the daemon are checked against their CID, as far as
+/// `check_block` can, before they are returned.
+#[cfg(feature = "blocking")]
+impl BlockSource for IPFS {
+    fn get_block(&self, cid: &Cid) -> Result<Vec<u8>, IPFSError> {
+        let mut res = self.request("block/get", &[("arg", cid.to_string())])?;
+        let mut block = Vec::new();
+        res.read_to_end(&mut block)?;
+        check_block(cid, &block)?;
+        Ok(block)
+    }
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+
+    use super::*;
+    use cid::Codec;
+    use multihash::HashFunction;
+    use serde_json::{self, Value};
+
+    #[test]
+    fn stat_is_parsed() {
+        let json = serde_json::from_str::<Value>(r#"{"Key":"bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq","Size":5}"#)
+                       .unwrap();
+        let stat = parse_stat(&json).unwrap();
+        assert_eq!(stat.size, 5);
+        assert_eq!(stat.cid.codec(), Codec::Raw);
+    }
+
+    #[test]
+    fn unsupported_hashes_are_accepted_unverified() {
+        let mut blake3 = vec![0x1e, 0x20];
+        blake3.extend_from_slice(&[7; 32]);
+        let cid = Cid::new_v1(Codec::Raw, blake3).unwrap();
+        assert!(check_block(&cid, b"hello").is_ok());
+        assert!(check_put(BlockStat { cid, size: 5 }, b"hello").is_ok());
+
+        let cid = Cid::for_block(Codec::Raw, HashFunction::Sha2_256, b"hello").unwrap();
+        assert!(check_block(&cid, b"hello").is_ok());
+        assert!(check_block(&cid, b"jello").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Stopping a long-running stream, such as a large `cat` or a streamed
+//! listing, from another thread or task.
+
+#[cfg(feature = "async")]
+use async_client::IpfsStream;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(feature = "async")]
+use futures_util::stream::Stream;
+#[cfg(feature = "async")]
+use futures_util::task::AtomicWaker;
+#[cfg(feature = "blocking")]
+use std::io;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "blocking")]
+use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+#[cfg(feature = "blocking")]
+use std::sync::Mutex;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+
+/// Cancels the stream it was taken from. Clones all cancel the same stream,
+/// so one can go to whichever thread or task decides it is no longer
+/// wanted.
+///
+/// Once cancelled, the stream gives a single `IPFSError::Cancelled` and
+/// ends, and a blocking reader fails. Either stops at once, even while it
+/// is waiting for the daemon.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    inner: Arc<Inner>
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    #[cfg(feature = "blocking")]
+    interrupt: Mutex<Option<SyncSender<io::Result<Vec<u8>>>>>,
+    #[cfg(feature = "async")]
+    waker: AtomicWaker
+}
+
+impl CancelHandle {
+    pub fn new() -> CancelHandle {
+        CancelHandle::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        // A full channel means the reader is not blocked, and it checks the
+        // flag before it next waits.
+        #[cfg(feature = "blocking")]
+        if let Some(ref sender) = *self.inner.interrupt.lock().unwrap() {
+            let _ = sender.try_send(Err(io::Error::other(IPFSError::Cancelled.to_string())));
+        }
+        #[cfg(feature = "async")]
+        self.inner.waker.wake();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Has `cancel` also send an error down `sender`, to wake a blocking
+    /// reader waiting on the other end.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn interrupt_with(&self, sender: SyncSender<io::Result<Vec<u8>>>) {
+        *self.inner.interrupt.lock().unwrap() = Some(sender);
+    }
+}
+
+/// A stream that ends with `IPFSError::Cancelled` when its handle is
+/// cancelled, even while it waits for the daemon.
+#[cfg(feature = "async")]
+pub struct Cancellable<T> {
+    stream: IpfsStream<T>,
+    cancel: CancelHandle,
+    finished: bool
+}
+
+#[cfg(feature = "async")]
+impl<T> Cancellable<T> {
+    pub fn new(stream: IpfsStream<T>, cancel: CancelHandle) -> Cancellable<T> {
+        Cancellable { stream, cancel, finished: false }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Stream for Cancellable<T> {
+    type Item = Result<T, IPFSError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Result<T, IPFSError>>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+        self.cancel.inner.waker.register(cx.waker());
+        if !self.cancel.is_cancelled() {
+            match self.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Err(_))) if self.cancel.is_cancelled() => {}
+                Poll::Ready(None) => {
+                    self.finished = true;
+                    return Poll::Ready(None);
+                }
+                poll => return poll
+            }
+        }
+        self.finished = true;
+        Poll::Ready(Some(Err(IPFSError::Cancelled)))
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod tests {
+
+    use super::*;
+    use futures_executor::block_on;
+    use futures_util::stream::{self, StreamExt};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn cancelling_wakes_a_waiting_stream() {
+        let cancel = CancelHandle::new();
+        let mut lines = Cancellable::new(Box::pin(stream::iter(vec![Ok(1)]).chain(stream::pending())), cancel.clone());
+        assert_eq!(block_on(lines.next()).unwrap().unwrap(), 1);
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            cancel.cancel();
+        });
+        match block_on(lines.next()) {
+            Some(Err(IPFSError::Cancelled)) => {}
+            other => panic!("unexpected item {:?}", other)
+        }
+        assert!(block_on(lines.next()).is_none());
+        canceller.join().unwrap();
+    }
+}
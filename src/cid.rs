@@ -0,0 +1,242 @@
+//! Content identifiers. A CIDv0 is a bare base58btc sha2-256 multihash
+//! (`Qm...`); a CIDv1 is `<multibase><version><multicodec><multihash>`.
+
+use error::IPFSError;
+use multibase::{self, Base};
+use multihash::{HashFunction, Multihash};
+use std::fmt;
+use std::str::FromStr;
+use varint;
+
+const SHA2_256: u64 = 0x12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Version {
+    V0,
+    V1
+}
+
+/// The multicodec naming how a block's bytes are to be interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Codec {
+    Raw,
+    DagPb,
+    DagCbor,
+    DagJson,
+    Libp2pKey,
+    Other(u64)
+}
+
+impl Codec {
+    pub fn from_code(code: u64) -> Codec {
+        match code {
+            0x55 => Codec::Raw,
+            0x70 => Codec::DagPb,
+            0x71 => Codec::DagCbor,
+            0x72 => Codec::Libp2pKey,
+            0x0129 => Codec::DagJson,
+            other => Codec::Other(other)
+        }
+    }
+
+    pub fn code(&self) -> u64 {
+        match *self {
+            Codec::Raw => 0x55,
+            Codec::DagPb => 0x70,
+            Codec::DagCbor => 0x71,
+            Codec::Libp2pKey => 0x72,
+            Codec::DagJson => 0x0129,
+            Codec::Other(code) => code
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cid {
+    version: Version,
+    codec: Codec,
+    hash: Vec<u8>
+}
+
+impl Cid {
+    /// Builds a CIDv0. Only dag-pb content hashed with sha2-256 can be named
+    /// by one.
+    pub fn new_v0(hash: Vec<u8>) -> Result<Cid, IPFSError> {
+        let multihash = Multihash::from_bytes(&hash)?;
+        if multihash.code() != SHA2_256 || multihash.digest().len() != 32 {
+            return Err(IPFSError::InvalidCid(format!("{} is not a sha2-256 multihash", Base::Base58Btc.encode(&hash))));
+        }
+        Ok(Cid { version: Version::V0, codec: Codec::DagPb, hash })
+    }
+
+    pub fn new_v1(codec: Codec, hash: Vec<u8>) -> Result<Cid, IPFSError> {
+        Multihash::from_bytes(&hash)?;
+        Ok(Cid { version: Version::V1, codec, hash })
+    }
+
+    /// Names `block` as a CIDv1 of the given codec, hashing it with `function`.
+    pub fn for_block(codec: Codec, function: HashFunction, block: &[u8]) -> Result<Cid, IPFSError> {
+        Ok(Cid { version: Version::V1, codec, hash: Multihash::new(function, block)?.to_bytes() })
+    }
+
+    /// Parses the binary form of a CID, as found in `PBLink.Hash`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Cid, IPFSError> {
+        // A CIDv0 is a bare multihash; its sha2-256 code 0x12 can never be a
+        // valid CID version.
+        if bytes.len() == 34 && bytes[0] as u64 == SHA2_256 && bytes[1] == 32 {
+            return Cid::new_v0(bytes.to_vec());
+        }
+
+        let invalid = || IPFSError::InvalidCid(Base::Base58Btc.encode(bytes));
+        let (version, version_len) = varint::decode(bytes).ok_or_else(invalid)?;
+        if version != 1 {
+            return Err(invalid());
+        }
+        let (codec, codec_len) = varint::decode(&bytes[version_len..]).ok_or_else(invalid)?;
+        let hash = &bytes[version_len + codec_len..];
+        Cid::new_v1(Codec::from_code(codec), hash.to_vec()).map_err(|_| invalid())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self.version {
+            Version::V0 => self.hash.clone(),
+            Version::V1 => {
+                let mut bytes = Vec::with_capacity(self.hash.len() + 4);
+                varint::encode(1, &mut bytes);
+                varint::encode(self.codec.code(), &mut bytes);
+                bytes.extend_from_slice(&self.hash);
+                bytes
+            }
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// The multihash of the content, including its function code and length
+    /// prefix.
+    pub fn hash(&self) -> &[u8] {
+        &self.hash
+    }
+
+    /// The multihash function code, e.g. 0x12 for sha2-256.
+    pub fn hash_code(&self) -> u64 {
+        self.multihash().code()
+    }
+
+    pub fn multihash(&self) -> Multihash {
+        Multihash::from_bytes(&self.hash).expect("CIDs are only built from valid multihashes")
+    }
+
+    /// Checks that `block` is the content this CID names.
+    pub fn verify(&self, block: &[u8]) -> Result<(), IPFSError> {
+        self.multihash().verify(block)
+    }
+
+    /// The same content named by a CIDv0, if it can be.
+    pub fn to_v0(&self) -> Result<Cid, IPFSError> {
+        if self.codec != Codec::DagPb {
+            return Err(IPFSError::InvalidCid(format!("{} is not dag-pb and has no CIDv0", self)));
+        }
+        Cid::new_v0(self.hash.clone())
+    }
+
+    pub fn to_v1(&self) -> Cid {
+        Cid { version: Version::V1, codec: self.codec, hash: self.hash.clone() }
+    }
+
+    /// Formats the CID in the given multibase. CIDv0 has no multibase prefix
+    /// and is always base58btc, whatever `base` is.
+    pub fn to_string_of_base(&self, base: Base) -> String {
+        match self.version {
+            Version::V0 => Base::Base58Btc.encode(&self.hash),
+            Version::V1 => multibase::encode(base, &self.to_bytes())
+        }
+    }
+}
+
+/// CIDv1 is written in base32, as go-ipfs and js-ipfs do by default.
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string_of_base(Base::Base32))
+    }
+}
+
+impl FromStr for Cid {
+    type Err = IPFSError;
+
+    /// Parses a CIDv0 (`Qm...`), or a CIDv1 in any multibase.
+    fn from_str(s: &str) -> Result<Cid, IPFSError> {
+        let invalid = |_| IPFSError::InvalidCid(s.to_string());
+        if s.len() == 46 && s.starts_with("Qm") {
+            return Cid::new_v0(Base::Base58Btc.decode(s).map_err(invalid)?);
+        }
+        let (_, bytes) = multibase::decode(s).map_err(invalid)?;
+        Cid::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn v0_round_trips() {
+        let cid: Cid = "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".parse().unwrap();
+        assert_eq!(cid.version(), Version::V0);
+        assert_eq!(cid.codec(), Codec::DagPb);
+        assert_eq!(cid.hash_code(), 0x12);
+        assert_eq!(cid.to_string(), "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt");
+        assert_eq!(Cid::from_bytes(&cid.to_bytes()).unwrap(), cid);
+    }
+
+    #[test]
+    fn v0_and_v1_convert() {
+        let v0: Cid = "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".parse().unwrap();
+        let v1 = v0.to_v1();
+        assert_eq!(v1.version(), Version::V1);
+        assert_eq!(&v1.to_bytes()[..2], &[0x01, 0x70]);
+        assert_eq!(v1.to_string(), "bafybeibi6uqgi5e7lou7b5rzzg5blncdduaidimywawttgy6qsmxpvprre");
+        assert_eq!(v1.to_string().parse::<Cid>().unwrap(), v1);
+        assert_eq!(v1.to_v0().unwrap(), v0);
+    }
+
+    #[test]
+    fn v1_parses_from_any_base() {
+        let cid: Cid = "bafybeibi6uqgi5e7lou7b5rzzg5blncdduaidimywawttgy6qsmxpvprre".parse().unwrap();
+        for &base in &[Base::Base32Upper, Base::Base36, Base::Base58Btc, Base::Base64Url, Base::Base16] {
+            assert_eq!(cid.to_string_of_base(base).parse::<Cid>().unwrap(), cid);
+        }
+        let key: Cid = "k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8".parse().unwrap();
+        assert_eq!(key.codec(), Codec::Libp2pKey);
+    }
+
+    #[test]
+    fn raw_cids_have_no_v0_form() {
+        let mut bytes = vec![0x01, 0x55, 0x12, 0x20];
+        bytes.extend_from_slice(&[7; 32]);
+        let cid = Cid::from_bytes(&bytes).unwrap();
+        assert_eq!(cid.codec(), Codec::Raw);
+        assert!(cid.to_v0().is_err());
+    }
+
+    #[test]
+    fn blocks_are_verified_against_their_cid() {
+        let cid = Cid::for_block(Codec::Raw, HashFunction::Sha2_256, b"hello").unwrap();
+        assert_eq!(cid.to_string(), "bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq");
+        assert!(cid.verify(b"hello").is_ok());
+        assert!(cid.verify(b"jello").is_err());
+    }
+
+    #[test]
+    fn truncated_cids_are_rejected() {
+        assert!(Cid::from_bytes(&[0x01, 0x70, 0x12, 0x20, 1, 2, 3]).is_err());
+        assert!("QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9t".parse::<Cid>().is_err());
+    }
+}
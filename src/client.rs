@@ -0,0 +1,329 @@
+//! Settings for the HTTP client behind `IPFS` and `AsyncIPFS`, for daemons
+//! reached through an authenticating proxy, over TLS, or both, and for how
+//! long and how often requests are tried.
+
+use error::IPFSError;
+use http::StatusCode;
+use multibase::Base;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+#[cfg(feature = "tls")]
+use reqwest::{Certificate, Identity};
+use std::cmp;
+use std::fmt;
+use std::time::Duration;
+
+/// The commands that only read from the daemon, which are safe to send
+/// again when an attempt fails.
+const READ_ONLY_COMMANDS: &[&str] = &["block/get", "block/stat", "cat", "dag/export", "dag/get", "dag/resolve",
+                                      "dag/stat", "files/ls", "files/read", "files/stat", "get", "key/export",
+                                      "key/list", "ls", "name/resolve", "object/diff", "object/get",
+                                      "object/links", "object/stat", "pin/ls", "pin/verify"];
+
+/// Applies the options to either client's builder; the two have the same
+/// methods but no common trait.
+macro_rules! build_client {
+    ($builder:expr, $options:expr) => {{
+        let builder = $builder.default_headers($options.default_headers()?);
+        let builder = match $options.connect_timeout {
+            Some(timeout) => builder.connect_timeout(timeout),
+            None => builder
+        };
+        #[cfg(feature = "tls")]
+        let builder = {
+            let mut builder = builder;
+            for certificate in $options.root_certificates()? {
+                builder = builder.add_root_certificate(certificate);
+            }
+            match $options.identity()? {
+                Some(identity) => builder.identity(identity),
+                None => builder
+            }
+        };
+        builder.build().map_err(|err| IPFSError::InvalidConfig(err.to_string()))
+    }}
+}
+
+/// Options for `IPFS::with_options` and `AsyncIPFS::with_options`.
+#[derive(Clone, Default)]
+pub struct ClientOptions {
+    headers: Vec<(String, String)>,
+    authorization: Option<String>,
+    #[cfg(feature = "tls")]
+    root_certificates: Vec<Vec<u8>>,
+    #[cfg(feature = "tls")]
+    identity: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    retry: RetryPolicy
+}
+
+impl ClientOptions {
+    pub fn new() -> ClientOptions {
+        ClientOptions::default()
+    }
+
+    /// Sends `name: value` with every request. Setting a header again adds
+    /// another value rather than replacing it.
+    pub fn header(mut self, name: &str, value: &str) -> ClientOptions {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Authenticates with HTTP basic authentication. Replaces any bearer
+    /// token.
+    pub fn basic_auth(mut self, user: &str, password: &str) -> ClientOptions {
+        let credentials = Base::Base64Pad.encode(format!("{}:{}", user, password).as_bytes());
+        self.authorization = Some(format!("Basic {}", credentials));
+        self
+    }
+
+    /// Authenticates with a bearer token. Replaces any basic credentials.
+    pub fn bearer_auth(mut self, token: &str) -> ClientOptions {
+        self.authorization = Some(format!("Bearer {}", token));
+        self
+    }
+
+    /// Also trusts the PEM-encoded CA certificates in `pem`, e.g. those of
+    /// a private CA the proxy's certificate is issued by.
+    #[cfg(feature = "tls")]
+    pub fn root_certificate(mut self, pem: &[u8]) -> ClientOptions {
+        self.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Presents a client certificate. `pem` holds the certificate chain and
+    /// its private key.
+    #[cfg(feature = "tls")]
+    pub fn client_identity(mut self, pem: &[u8]) -> ClientOptions {
+        self.identity = Some(pem.to_vec());
+        self
+    }
+
+    /// Gives up on a request when the daemon has sent nothing for
+    /// `timeout`, whether it has yet to answer or has stalled partway
+    /// through a response. A download that keeps arriving is not cut short,
+    /// but a stream that waits on the daemon, such as `pin/verify`, needs a
+    /// timeout longer than its quietest spell. There is none by default.
+    pub fn timeout(mut self, timeout: Duration) -> ClientOptions {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Gives up on connecting to the daemon after `timeout`.
+    pub fn connect_timeout(mut self, timeout: Duration) -> ClientOptions {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Retries failed requests as `policy` allows. By default nothing is
+    /// retried.
+    pub fn retry(mut self, policy: RetryPolicy) -> ClientOptions {
+        self.retry = policy;
+        self
+    }
+
+    pub(crate) fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    fn default_headers(&self) -> Result<HeaderMap, IPFSError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| IPFSError::InvalidConfig(format!("invalid header name {:?}", name)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| IPFSError::InvalidConfig(format!("invalid value for header {}", name)))?;
+            headers.append(name, value);
+        }
+        if let Some(ref authorization) = self.authorization {
+            let mut value = HeaderValue::from_str(authorization)
+                .map_err(|_| IPFSError::InvalidConfig("invalid credentials".to_string()))?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(headers)
+    }
+
+    #[cfg(feature = "tls")]
+    fn root_certificates(&self) -> Result<Vec<Certificate>, IPFSError> {
+        let mut certificates = Vec::new();
+        for pem in &self.root_certificates {
+            certificates.extend(Certificate::from_pem_bundle(pem).map_err(|err| {
+                IPFSError::InvalidConfig(format!("invalid root certificate: {}", err))
+            })?);
+        }
+        Ok(certificates)
+    }
+
+    #[cfg(feature = "tls")]
+    fn identity(&self) -> Result<Option<Identity>, IPFSError> {
+        match self.identity {
+            Some(ref pem) => match Identity::from_pem(pem) {
+                Ok(identity) => Ok(Some(identity)),
+                Err(err) => Err(IPFSError::InvalidConfig(format!("invalid client identity: {}", err)))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// The blocking client's timeout bounds the wait for the response and
+    /// then each read of its body, which is what `timeout` promises.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn blocking_client(&self) -> Result<reqwest::blocking::Client, IPFSError> {
+        build_client!(reqwest::blocking::Client::builder().timeout(self.timeout), self)
+    }
+
+    /// The async client's own timeout covers the whole request, body
+    /// included, so its read timeout is the one that matches.
+    #[cfg(feature = "async")]
+    pub(crate) fn async_client(&self) -> Result<reqwest::Client, IPFSError> {
+        let builder = match self.timeout {
+            Some(timeout) => reqwest::Client::builder().read_timeout(timeout),
+            None => reqwest::Client::builder()
+        };
+        build_client!(builder, self)
+    }
+}
+
+/// Credentials and keys are left out.
+impl fmt::Debug for ClientOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("ClientOptions");
+        debug.field("headers", &self.headers.iter().map(|(name, _)| name).collect::<Vec<_>>())
+             .field("authorization", &self.authorization.is_some());
+        #[cfg(feature = "tls")]
+        debug.field("root_certificates", &self.root_certificates.len())
+             .field("identity", &self.identity.is_some());
+        debug.field("timeout", &self.timeout)
+             .field("connect_timeout", &self.connect_timeout)
+             .field("retry", &self.retry)
+             .finish()
+    }
+}
+
+/// How requests that failed in transit are retried, waiting longer after
+/// each attempt: `initial_backoff`, then that times `multiplier`, and so on
+/// up to `max_backoff`. Only commands that read from the daemon without
+/// changing anything, such as `cat`, `ls` or `block/get`, are sent again,
+/// and only when the daemon could not be reached, timed out, or answered
+/// 502, 503 or 504 as a proxy in front of a restarting daemon does. A
+/// response that fails partway through its body is not retried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that retries nothing until `max_retries` is set, and then
+    /// backs off from 100ms, doubling up to 10s.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// How many times a request is sent again after its first attempt.
+    pub fn max_retries(mut self, retries: u32) -> RetryPolicy {
+        self.max_retries = retries;
+        self
+    }
+
+    /// The wait before the first retry, and the longest wait between any
+    /// two.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> RetryPolicy {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// How much longer each wait is than the one before.
+    pub fn multiplier(mut self, multiplier: f64) -> RetryPolicy {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// How long to wait before sending `cmd` again after attempt number
+    /// `attempt`, counting from zero, failed with `err`; `None` if it should
+    /// not be retried.
+    pub(crate) fn retry_after(&self, cmd: &str, attempt: u32, err: &IPFSError) -> Option<Duration> {
+        if attempt >= self.max_retries || !READ_ONLY_COMMANDS.contains(&cmd) || !is_transient(err) {
+            return None;
+        }
+        let factor = self.multiplier.max(1.0).powi(cmp::min(attempt, i32::MAX as u32) as i32);
+        let backoff = (self.initial_backoff.as_secs_f64() * factor).min(self.max_backoff.as_secs_f64());
+        Some(Duration::from_secs_f64(backoff))
+    }
+}
+
+/// Whether a request failed in a way that sending it again could fix.
+fn is_transient(err: &IPFSError) -> bool {
+    let status = match *err {
+        IPFSError::ConnectionError(_) => return true,
+        IPFSError::Api(ref err) => err.status,
+        IPFSError::Http(status, _) => status,
+        _ => return false
+    };
+    status == StatusCode::BAD_GATEWAY || status == StatusCode::SERVICE_UNAVAILABLE
+        || status == StatusCode::GATEWAY_TIMEOUT
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn credentials_become_an_authorization_header() {
+        let headers = ClientOptions::new().header("X-Cluster", "eu-1")
+                                          .header("X-Cluster", "eu-2")
+                                          .bearer_auth("stale")
+                                          .basic_auth("user", "pass")
+                                          .default_headers()
+                                          .unwrap();
+        assert_eq!(headers.get_all("x-cluster").iter().collect::<Vec<_>>(), vec!["eu-1", "eu-2"]);
+        assert_eq!(headers[AUTHORIZATION], "Basic dXNlcjpwYXNz");
+        assert!(headers[AUTHORIZATION].is_sensitive());
+        assert!(!format!("{:?}", ClientOptions::new().bearer_auth("secret")).contains("secret"));
+    }
+
+    #[test]
+    fn backoff_grows_to_its_cap() {
+        let policy = RetryPolicy::new().max_retries(4)
+                                       .backoff(Duration::from_millis(100), Duration::from_millis(500));
+        let err = IPFSError::ConnectionError("connection refused".into());
+        let waits: Vec<_> = (0..5).map(|attempt| policy.retry_after("cat", attempt, &err)).collect();
+        assert_eq!(waits, vec![Some(Duration::from_millis(100)), Some(Duration::from_millis(200)),
+                               Some(Duration::from_millis(400)), Some(Duration::from_millis(500)), None]);
+    }
+
+    #[test]
+    fn only_reads_that_failed_in_transit_are_retried() {
+        let policy = RetryPolicy::new().max_retries(1);
+        let unavailable = IPFSError::from_response(StatusCode::SERVICE_UNAVAILABLE, String::new());
+        let failed = IPFSError::from_response(StatusCode::INTERNAL_SERVER_ERROR, String::new());
+        assert!(policy.retry_after("block/get", 0, &unavailable).is_some());
+        assert!(policy.retry_after("block/get", 0, &failed).is_none());
+        assert!(policy.retry_after("pin/add", 0, &unavailable).is_none());
+        assert!(RetryPolicy::new().retry_after("block/get", 0, &unavailable).is_none());
+    }
+
+    #[test]
+    fn invalid_headers_are_rejected() {
+        match ClientOptions::new().header("bad header", "x").default_headers() {
+            Err(IPFSError::InvalidConfig(_)) => {}
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+}
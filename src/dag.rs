@@ -0,0 +1,220 @@
+use cid::Cid;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use ipld::Ipld;
+#[cfg(feature = "blocking")]
+use multipart::Multipart;
+#[cfg(feature = "blocking")]
+use response::{read_json, string_field, JsonLines};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::u64_field;
+#[cfg(feature = "blocking")]
+use response::ResponseReader;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+#[cfg(feature = "blocking")]
+use std::io::{Cursor, Read};
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// Options for `dag_put`. Anything left unset uses the daemon's default,
+/// which stores dag-cbor hashed with sha2-256.
+#[derive(Debug, Clone, Default)]
+pub struct DagPutOptions {
+    store_codec: Option<String>,
+    input_codec: Option<String>,
+    hash: Option<String>,
+    pin: Option<bool>
+}
+
+impl DagPutOptions {
+    pub fn new() -> DagPutOptions {
+        DagPutOptions::default()
+    }
+
+    /// Codec the node is stored with, e.g. `dag-cbor`, `dag-json` or `dag-pb`.
+    pub fn store_codec(mut self, codec: &str) -> DagPutOptions {
+        self.store_codec = Some(codec.to_string());
+        self
+    }
+
+    /// Codec the bytes given to `dag_put_raw` are in. `dag_put` always sends
+    /// DAG-JSON and ignores this.
+    pub fn input_codec(mut self, codec: &str) -> DagPutOptions {
+        self.input_codec = Some(codec.to_string());
+        self
+    }
+
+    /// Multihash function name, e.g. `sha2-256` or `blake2b-256`.
+    pub fn hash(mut self, hash: &str) -> DagPutOptions {
+        self.hash = Some(hash.to_string());
+        self
+    }
+
+    pub fn pin(mut self, pin: bool) -> DagPutOptions {
+        self.pin = Some(pin);
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = Vec::new();
+        if let Some(ref codec) = self.store_codec {
+            args.push(("store-codec", codec.clone()));
+        }
+        if let Some(ref codec) = self.input_codec {
+            args.push(("input-codec", codec.clone()));
+        }
+        if let Some(ref hash) = self.hash {
+            args.push(("hash", hash.clone()));
+        }
+        if let Some(pin) = self.pin {
+            args.push(("pin", pin.to_string()));
+        }
+        args
+    }
+}
+
+/// Where `dag_resolve` got to: the last block on the path, and the part of
+/// the path that lies inside that block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DagResolved {
+    pub cid: Cid,
+    pub rem_path: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DagStat {
+    /// Total size of the distinct blocks in the DAG.
+    pub size: u64,
+    pub num_blocks: u64
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Fetches the IPLD node at `path`, which may descend into the node's
+    /// fields (`/ipfs/<cid>/a/b`), whatever codec it is stored with.
+    pub fn dag_get(&self, path: &str) -> Result<Ipld, IPFSError> {
+        let args = [("arg", self.resolve_path(path)?), ("output-codec", "dag-json".to_string())];
+        Ipld::from_dag_json(&read_json(self.request("dag/get", &args)?)?)
+    }
+
+    /// Stores `value` and returns its CID.
+    pub fn dag_put(&self, value: &Ipld, options: &DagPutOptions) -> Result<Cid, IPFSError> {
+        let options = options.clone().input_codec("dag-json");
+        self.dag_put_raw(Cursor::new(value.to_dag_json()?.to_string()), &options)
+    }
+
+    /// Stores a node already encoded in the options' input codec, such as the
+    /// bytes of a dag-cbor block.
+    pub fn dag_put_raw<R: Read>(&self, data: R, options: &DagPutOptions) -> Result<Cid, IPFSError> {
+        let mut form = Multipart::new();
+        form.add_reader("object data", data);
+        let json = read_json(self.request_with_body("dag/put", &options.to_args(), &mut form)?)?;
+        link_field(&json, "Cid")
+    }
+
+    pub fn dag_resolve(&self, path: &str) -> Result<DagResolved, IPFSError> {
+        let json = read_json(self.request("dag/resolve", &[("arg", self.resolve_path(path)?)])?)?;
+        Ok(DagResolved { cid: link_field(&json, "Cid")?, rem_path: string_field(&json, "RemPath")? })
+    }
+
+    /// Walks the whole DAG below `path`, fetching any blocks that are not
+    /// local.
+    pub fn dag_stat(&self, path: &str) -> Result<DagStat, IPFSError> {
+        let args = [("arg", self.resolve_path(path)?), ("progress", "false".to_string())];
+        let mut last = None;
+        for line in JsonLines::new(self.request("dag/stat", &args)?) {
+            last = Some(line?);
+        }
+        final_stat(last)
+    }
+
+    /// Streams the DAG below `cid` as a CAR file.
+    pub fn dag_export(&self, cid: &Cid) -> Result<ResponseReader, IPFSError> {
+        Ok(ResponseReader::new(self.request("dag/export", &[("arg", cid.to_string())])?))
+    }
+
+    /// Imports the blocks of a CAR file read from `car`, returning its roots.
+    /// They are pinned recursively if `pin_roots` is set.
+    pub fn dag_import<R: Read>(&self, car: R, pin_roots: bool) -> Result<Vec<Cid>, IPFSError> {
+        let mut form = Multipart::new();
+        form.add_reader("file", car);
+        let res = self.request_with_body("dag/import", &[("pin-roots", pin_roots.to_string())], &mut form)?;
+        let mut roots = Vec::new();
+        for line in JsonLines::new(res) {
+            roots.extend(parse_root(&line?)?);
+        }
+        Ok(roots)
+    }
+}
+
+/// Reads a field holding a DAG-JSON link, `{"/": "<cid>"}`.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn link_field(json: &Value, key: &str) -> Result<Cid, IPFSError> {
+    match json.get(key).and_then(|link| link.get("/")).and_then(|cid| cid.as_str()) {
+        Some(cid) => cid.parse(),
+        None => Err(IPFSError::UnexpectedResponse(format!("missing link field {:?}", key)))
+    }
+}
+
+/// Reads a line of `dag/import` output, which names a root of the CAR file or
+/// reports some other progress.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_root(line: &Value) -> Result<Option<Cid>, IPFSError> {
+    let root = match line.get("Root") {
+        Some(root) => root,
+        None => return Ok(None)
+    };
+    if let Some(error) = root.get("PinErrorMsg").and_then(|e| e.as_str()).filter(|e| !e.is_empty()) {
+        return Err(IPFSError::UnexpectedResponse(format!("pinning {} failed: {}", link_field(root, "Cid")?, error)));
+    }
+    Ok(Some(link_field(root, "Cid")?))
+}
+
+/// `dag/stat` reports its running totals line by line; the last holds the
+/// result.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn final_stat(last: Option<Value>) -> Result<DagStat, IPFSError> {
+    match last {
+        Some(json) => parse_stat(&json),
+        None => Err(IPFSError::UnexpectedResponse("dag/stat returned nothing".to_string()))
+    }
+}
+
+/// Newer daemons report per-root stats under `DagStats`; older ones put the
+/// figures at the top level.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn parse_stat(json: &Value) -> Result<DagStat, IPFSError> {
+    let stat = json.get("DagStats").and_then(|s| s.as_array()).and_then(|s| s.first()).unwrap_or(json);
+    Ok(DagStat { size: u64_field(stat, "Size")?, num_blocks: u64_field(stat, "NumBlocks")? })
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+
+    use super::*;
+    use serde_json::{self, Value};
+
+    #[test]
+    fn stats_are_parsed_in_either_shape() {
+        let old = serde_json::from_str::<Value>(r#"{"Size":1024,"NumBlocks":3}"#).unwrap();
+        let new = serde_json::from_str::<Value>(r#"{"TotalSize":1024,"DagStats":[{"Cid":{"/":"bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq"},
+                                                                  "Size":1024,"NumBlocks":3}]}"#).unwrap();
+        assert_eq!(parse_stat(&old).unwrap(), DagStat { size: 1024, num_blocks: 3 });
+        assert_eq!(parse_stat(&new).unwrap(), DagStat { size: 1024, num_blocks: 3 });
+    }
+
+    #[test]
+    fn import_roots_are_parsed() {
+        let root = serde_json::from_str::<Value>(r#"{"Root":{"Cid":{"/":"bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq"},
+                                                  "PinErrorMsg":""}}"#).unwrap();
+        assert!(parse_root(&root).unwrap().is_some());
+        let stats = serde_json::from_str::<Value>(r#"{"Stats":{"BlockCount":3,"BlockBytesCount":1024}}"#).unwrap();
+        assert_eq!(parse_root(&stats).unwrap(), None);
+        let failed = serde_json::from_str::<Value>(r#"{"Root":{"Cid":{"/":"bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq"},
+                                                    "PinErrorMsg":"blockstore: block not found"}}"#).unwrap();
+        assert!(parse_root(&failed).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+//! Canonical dag-pb encoding. Protobuf allows the same node to be written in
+//! several ways, each with its own CID; the dag-pb spec pins down one of
+//! them, which is what go-ipfs and kubo produce:
+//!
+//! * a node's `Links` come before its `Data`, and `Data` is omitted if unset;
+//! * links are sorted by the bytes of their names (a stable sort, so links
+//!   with equal names keep their order);
+//! * a link's `Hash` is required and its fields appear in field order;
+//! * integers use minimal varints, and no other fields may appear.
+
+use cid::Cid;
+use error::IPFSError;
+use merkledag::{PBLink, PBNode};
+use protobuf::RepeatedField;
+use varint;
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_BYTES: u64 = 2;
+
+/// Builds a dag-pb node link by link.
+#[derive(Debug, Clone, Default)]
+pub struct DagPbBuilder {
+    links: Vec<PBLink>,
+    data: Option<Vec<u8>>
+}
+
+impl DagPbBuilder {
+    pub fn new() -> DagPbBuilder {
+        DagPbBuilder::default()
+    }
+
+    /// Adds a link to `cid` called `name`, where `tsize` is the cumulative
+    /// size of the target. Links may be added in any order.
+    pub fn link(mut self, name: &str, cid: &Cid, tsize: u64) -> DagPbBuilder {
+        let mut link = PBLink::new();
+        link.set_Hash(cid.to_bytes());
+        link.set_Name(name.to_string());
+        link.set_Tsize(tsize);
+        self.links.push(link);
+        self
+    }
+
+    pub fn data(mut self, data: Vec<u8>) -> DagPbBuilder {
+        self.data = Some(data);
+        self
+    }
+
+    /// The node, with its links in canonical order.
+    pub fn build(self) -> PBNode {
+        let mut links = self.links;
+        sort_links(&mut links);
+        let mut node = PBNode::new();
+        node.set_Links(RepeatedField::from_vec(links));
+        if let Some(data) = self.data {
+            node.set_Data(data);
+        }
+        node
+    }
+
+    /// The node's canonical block, ready to be hashed with `Cid::for_block`
+    /// or stored with `block_put`.
+    pub fn encode(self) -> Vec<u8> {
+        encode_dag_pb(&self.build()).expect("every link added by the builder has a hash")
+    }
+}
+
+/// Encodes `node` canonically, sorting its links if they are not already in
+/// order. Fails if a link has no hash.
+pub fn encode_dag_pb(node: &PBNode) -> Result<Vec<u8>, IPFSError> {
+    let mut links = node.get_Links().to_vec();
+    sort_links(&mut links);
+
+    let mut block = Vec::new();
+    for link in &links {
+        if !link.has_Hash() {
+            return Err(IPFSError::InvalidBlock(format!("link {:?} has no hash", link.get_Name())));
+        }
+        let mut encoded = Vec::new();
+        write_bytes(&mut encoded, 1, link.get_Hash());
+        if link.has_Name() {
+            write_bytes(&mut encoded, 2, link.get_Name().as_bytes());
+        }
+        if link.has_Tsize() {
+            varint::encode(3 << 3 | WIRE_VARINT, &mut encoded);
+            varint::encode(link.get_Tsize(), &mut encoded);
+        }
+        write_bytes(&mut block, 2, &encoded);
+    }
+    if node.has_Data() {
+        write_bytes(&mut block, 1, node.get_Data());
+    }
+    Ok(block)
+}
+
+/// Decodes a dag-pb block, rejecting anything that is not in canonical form
+/// and so would not re-encode to the same bytes.
+pub fn decode_dag_pb(block: &[u8]) -> Result<PBNode, IPFSError> {
+    let mut node = PBNode::new();
+    let mut rest = block;
+    while !rest.is_empty() {
+        let (field, value) = read_bytes_field(&mut rest)?;
+        match field {
+            2 if node.has_Data() => return Err(invalid("Links after Data")),
+            2 => {
+                let link = decode_link(value)?;
+                if let Some(last) = node.get_Links().last() {
+                    if last.get_Name().as_bytes() > link.get_Name().as_bytes() {
+                        return Err(invalid("links are not sorted by name"));
+                    }
+                }
+                node.mut_Links().push(link);
+            }
+            1 if node.has_Data() => return Err(invalid("duplicate Data")),
+            1 => node.set_Data(value.to_vec()),
+            _ => return Err(invalid(&format!("unexpected PBNode field {}", field)))
+        }
+    }
+    Ok(node)
+}
+
+fn decode_link(mut rest: &[u8]) -> Result<PBLink, IPFSError> {
+    let mut link = PBLink::new();
+    let mut last_field = 0;
+    while !rest.is_empty() {
+        let (tag, len) = varint::decode(rest).ok_or_else(|| invalid("bad PBLink field tag"))?;
+        rest = &rest[len..];
+        let field = tag >> 3;
+        if field <= last_field {
+            return Err(invalid("PBLink fields out of order or repeated"));
+        }
+        last_field = field;
+        match (field, tag & 7) {
+            (1, WIRE_BYTES) => {
+                let hash = take_bytes(&mut rest)?;
+                Cid::from_bytes(hash)?;
+                link.set_Hash(hash.to_vec());
+            }
+            (2, WIRE_BYTES) => {
+                let name = String::from_utf8(take_bytes(&mut rest)?.to_vec())
+                               .map_err(|err| IPFSError::Utf8(err.utf8_error()))?;
+                link.set_Name(name);
+            }
+            (3, WIRE_VARINT) => {
+                let (tsize, len) = varint::decode(rest).ok_or_else(|| invalid("bad Tsize"))?;
+                rest = &rest[len..];
+                link.set_Tsize(tsize);
+            }
+            _ => return Err(invalid(&format!("unexpected PBLink field {} with wire type {}", field, tag & 7)))
+        }
+    }
+    if !link.has_Hash() {
+        return Err(invalid("PBLink without Hash"));
+    }
+    Ok(link)
+}
+
+fn sort_links(links: &mut [PBLink]) {
+    links.sort_by(|a, b| a.get_Name().as_bytes().cmp(b.get_Name().as_bytes()));
+}
+
+fn write_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    varint::encode(field << 3 | WIRE_BYTES, buf);
+    varint::encode(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+/// Reads a length-delimited field, the only wire type a PBNode has.
+fn read_bytes_field<'a>(rest: &mut &'a [u8]) -> Result<(u64, &'a [u8]), IPFSError> {
+    let (tag, len) = varint::decode(rest).ok_or_else(|| invalid("bad PBNode field tag"))?;
+    *rest = &rest[len..];
+    if tag & 7 != WIRE_BYTES {
+        return Err(invalid(&format!("PBNode field {} has wire type {}", tag >> 3, tag & 7)));
+    }
+    Ok((tag >> 3, take_bytes(rest)?))
+}
+
+fn take_bytes<'a>(rest: &mut &'a [u8]) -> Result<&'a [u8], IPFSError> {
+    let (n, len) = varint::decode(rest).ok_or_else(|| invalid("bad length"))?;
+    let body = &rest[len..];
+    if n > body.len() as u64 {
+        return Err(invalid("truncated field"));
+    }
+    let (bytes, after) = body.split_at(n as usize);
+    *rest = after;
+    Ok(bytes)
+}
+
+fn invalid(what: &str) -> IPFSError {
+    IPFSError::InvalidBlock(format!("non-canonical dag-pb: {}", what))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use cid::Codec;
+    use multihash::HashFunction;
+
+    fn v0(block: &[u8]) -> String {
+        Cid::for_block(Codec::DagPb, HashFunction::Sha2_256, block).unwrap().to_v0().unwrap().to_string()
+    }
+
+    #[test]
+    fn empty_directory_matches_go_ipfs() {
+        let block = DagPbBuilder::new().data(vec![0x08, 0x01]).encode();
+        assert_eq!(block, vec![0x0a, 0x02, 0x08, 0x01]);
+        assert_eq!(v0(&block), "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn");
+    }
+
+    #[test]
+    fn links_are_sorted_and_come_before_data() {
+        let file: Cid = "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".parse().unwrap();
+        let block = DagPbBuilder::new().data(vec![0x08, 0x01])
+                                       .link("b", &file, 23)
+                                       .link("a", &file, 23)
+                                       .encode();
+        let node = decode_dag_pb(&block).unwrap();
+        let names: Vec<&str> = node.get_Links().iter().map(|l| l.get_Name()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(node.get_Data(), &[0x08, 0x01]);
+        assert_eq!(encode_dag_pb(&node).unwrap(), block);
+        assert_eq!(block[0], 0x12);
+    }
+
+    #[test]
+    fn non_canonical_blocks_are_rejected() {
+        let file: Cid = "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".parse().unwrap();
+        let block = DagPbBuilder::new().link("a", &file, 23).link("b", &file, 23).encode();
+        let link_len = block[1] as usize + 2;
+
+        let mut unsorted = block[link_len..].to_vec();
+        unsorted.extend_from_slice(&block[..link_len]);
+        assert!(decode_dag_pb(&unsorted).is_err());
+
+        let mut data_first = vec![0x0a, 0x00];
+        data_first.extend_from_slice(&block);
+        assert!(decode_dag_pb(&data_first).is_err());
+
+        assert!(decode_dag_pb(&[0x1a, 0x00]).is_err());
+        assert!(decode_dag_pb(&[0x0a, 0x05, 0x00]).is_err());
+    }
+}
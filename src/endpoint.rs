@@ -0,0 +1,177 @@
+//! Where a daemon's HTTP API is: a base URL such as
+//! `http://127.0.0.1:5001/api/v0/`, given directly, as a multiaddr, or read
+//! from the `api` file a running daemon writes into its repo.
+
+use error::IPFSError;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use url::Url;
+
+const API_PATH: &str = "/api/v0/";
+
+/// The base URL API commands are sent to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    base: Url
+}
+
+impl Endpoint {
+    /// `http://<host>:<port>/api/v0/`.
+    pub fn new(host: &str, port: u16) -> Endpoint {
+        let host = if host.contains(':') && !host.starts_with('[') { format!("[{}]", host) } else { host.to_string() };
+        let base = Url::parse(&format!("http://{}:{}{}", host, port, API_PATH))
+            .expect("host and port always form a valid URL");
+        Endpoint { base }
+    }
+
+    /// An `http` or `https` URL. One without a path gets the default
+    /// `/api/v0/`; otherwise the path is taken to be the API's, as when the
+    /// daemon sits behind a proxy at `https://gateway.example/ipfs/api/v0`.
+    pub fn from_url(url: &str) -> Result<Endpoint, IPFSError> {
+        let mut base = match Url::parse(url) {
+            Ok(base) => base,
+            Err(_) => return Err(IPFSError::InvalidEndpoint(url.to_string()))
+        };
+        if (base.scheme() != "http" && base.scheme() != "https") || base.host_str().is_none() {
+            return Err(IPFSError::InvalidEndpoint(url.to_string()));
+        }
+        let path = if base.path() == "/" {
+            API_PATH.to_string()
+        } else {
+            format!("{}/", base.path().trim_end_matches('/'))
+        };
+        base.set_path(&path);
+        base.set_query(None);
+        base.set_fragment(None);
+        Ok(Endpoint { base })
+    }
+
+    /// A multiaddr such as `/ip4/127.0.0.1/tcp/5001` or
+    /// `/dns4/node.example/tcp/443/https`. Plain HTTP is assumed unless the
+    /// address ends in `/https` or `/tls/http`.
+    pub fn from_multiaddr(addr: &str) -> Result<Endpoint, IPFSError> {
+        let invalid = || IPFSError::InvalidEndpoint(addr.to_string());
+        let mut parts = addr.trim().trim_end_matches('/').split('/');
+        if parts.next() != Some("") {
+            return Err(invalid());
+        }
+        let host = match (parts.next(), parts.next()) {
+            (Some("ip4"), Some(ip)) => ip.parse::<Ipv4Addr>().map_err(|_| invalid())?.to_string(),
+            (Some("ip6"), Some(ip)) => format!("[{}]", ip.parse::<Ipv6Addr>().map_err(|_| invalid())?),
+            (Some("dns"), Some(name)) | (Some("dns4"), Some(name)) | (Some("dns6"), Some(name))
+                if !name.is_empty() => name.to_string(),
+            _ => return Err(invalid())
+        };
+        let port: u16 = match (parts.next(), parts.next()) {
+            (Some("tcp"), Some(port)) => port.parse().map_err(|_| invalid())?,
+            _ => return Err(invalid())
+        };
+        let scheme = match parts.collect::<Vec<_>>().as_slice() {
+            [] | ["http"] => "http",
+            ["https"] | ["tls", "http"] => "https",
+            _ => return Err(invalid())
+        };
+        Endpoint::from_url(&format!("{}://{}:{}{}", scheme, host, port, API_PATH))
+    }
+
+    /// Finds a local daemon as the Go and JS clients do, through the `api`
+    /// file in `$IPFS_PATH`, or in `~/.ipfs` if that is unset.
+    pub fn discover() -> Result<Endpoint, IPFSError> {
+        let repo = match env::var_os("IPFS_PATH").filter(|path| !path.is_empty()) {
+            Some(path) => PathBuf::from(path),
+            None => match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+                Some(home) => PathBuf::from(home).join(".ipfs"),
+                None => return Err(IPFSError::InvalidEndpoint("no IPFS_PATH or home directory".to_string()))
+            }
+        };
+        Endpoint::from_repo(&repo)
+    }
+
+    /// Reads the multiaddr the daemon running on the repo at `repo` has
+    /// written to its `api` file.
+    pub fn from_repo(repo: &Path) -> Result<Endpoint, IPFSError> {
+        let mut addr = String::new();
+        File::open(repo.join("api"))?.read_to_string(&mut addr)?;
+        Endpoint::from_multiaddr(&addr)
+    }
+
+    /// The base URL, ending in a slash.
+    pub fn as_str(&self) -> &str {
+        self.base.as_str()
+    }
+
+    /// The URL of an API command, with its arguments in the query string.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn url(&self, cmd: &str, args: &[(&str, String)]) -> Url {
+        let mut url = self.base.join(cmd).expect("commands are always valid relative URLs");
+        for &(key, ref value) in args {
+            url.query_pairs_mut().append_pair(key, value);
+        }
+        url
+    }
+}
+
+/// Parses a multiaddr if the string starts with a slash, and a URL otherwise.
+impl FromStr for Endpoint {
+    type Err = IPFSError;
+
+    fn from_str(s: &str) -> Result<Endpoint, IPFSError> {
+        if s.starts_with('/') {
+            Endpoint::from_multiaddr(s)
+        } else {
+            Endpoint::from_url(s)
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn multiaddrs_are_converted() {
+        let cases = [("/ip4/127.0.0.1/tcp/5001", "http://127.0.0.1:5001/api/v0/"),
+                     ("/ip6/::1/tcp/5001/http", "http://[::1]:5001/api/v0/"),
+                     ("/dns4/node.example/tcp/443/https", "https://node.example/api/v0/"),
+                     ("/dns/node.example/tcp/8443/tls/http", "https://node.example:8443/api/v0/")];
+        for &(addr, url) in &cases {
+            assert_eq!(Endpoint::from_multiaddr(addr).unwrap().as_str(), url);
+        }
+        assert!(Endpoint::from_multiaddr("/ip4/127.0.0.1/udp/5001").is_err());
+        assert!(Endpoint::from_multiaddr("/unix/var/run/ipfs.sock").is_err());
+        assert!(Endpoint::from_multiaddr("/ip4/127.0.0.1/tcp/5001/ws").is_err());
+    }
+
+    #[test]
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn urls_keep_their_base_path() {
+        assert_eq!(Endpoint::from_url("http://localhost:5001").unwrap().as_str(), "http://localhost:5001/api/v0/");
+        let proxied = "https://gateway.example/ipfs/api/v0".parse::<Endpoint>().unwrap();
+        assert_eq!(proxied.url("files/ls", &[("arg", "/a b".to_string())]).as_str(),
+                   "https://gateway.example/ipfs/api/v0/files/ls?arg=%2Fa+b");
+        assert!(Endpoint::from_url("ftp://localhost/").is_err());
+    }
+
+    #[test]
+    fn repo_api_file_is_read() {
+        let repo = env::temp_dir().join(format!("ipfs-api-repo-{}", ::std::process::id()));
+        fs::create_dir_all(&repo).unwrap();
+        File::create(repo.join("api")).unwrap().write_all(b"/ip4/127.0.0.1/tcp/5002\n").unwrap();
+        assert_eq!(Endpoint::from_repo(&repo).unwrap(), Endpoint::new("127.0.0.1", 5002));
+        fs::remove_dir_all(&repo).unwrap();
+    }
+}
@@ -0,0 +1,207 @@
+use http::StatusCode;
+use multihash::Multihash;
+use protobuf::ProtobufError;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use reqwest;
+use serde_json::{self, Value};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::Utf8Error;
+
+/// An error body returned by the daemon, e.g.
+/// `{"Message": "invalid path", "Code": 0, "Type": "error"}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    /// The HTTP status the error was returned with.
+    pub status: StatusCode,
+    pub message: String,
+    pub code: i64,
+    pub kind: String
+}
+
+impl ApiError {
+    /// Parses a daemon error body, returning `None` if it is not the JSON
+    /// error object the daemon normally sends.
+    pub fn from_body(status: StatusCode, body: &str) -> Option<ApiError> {
+        let json = match serde_json::from_str::<Value>(body) {
+            Ok(json) => json,
+            Err(_) => return None
+        };
+        let message = match json.get("Message").and_then(|m| m.as_str()) {
+            Some(message) => message.to_string(),
+            None => return None
+        };
+        Some(ApiError {
+            status,
+            message,
+            code: json.get("Code").and_then(|c| c.as_i64()).unwrap_or(0),
+            kind: json.get("Type").and_then(|t| t.as_str()).unwrap_or("error").to_string()
+        })
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.status, self.kind, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum IPFSError {
+    /// The daemon could not be reached, or the connection failed mid-request.
+    ConnectionError(Box<dyn Error + Send + Sync>),
+    /// The daemon answered with an error status and a body that was not a
+    /// JSON error object. The raw body is kept for diagnostics.
+    Http(StatusCode, String),
+    /// The daemon answered with a structured error.
+    Api(ApiError),
+    /// A response body could not be decoded as the expected protobuf message.
+    Protobuf(ProtobufError),
+    /// A response body could not be parsed as JSON.
+    Json(serde_json::Error),
+    /// A response parsed, but did not have the shape the command documents.
+    UnexpectedResponse(String),
+    /// A block did not decode to the structure its position in a DAG requires.
+    InvalidBlock(String),
+    /// A string could not be parsed as a CID.
+    InvalidCid(String),
+    /// A string was not valid multibase, or used a base this crate lacks.
+    InvalidMultibase(String),
+    /// Bytes that should have been a multihash did not decode as one.
+    InvalidMultihash(Vec<u8>),
+    /// A chunker specification such as `size-262144` could not be parsed.
+    InvalidChunker(String),
+    /// An API address was not a usable multiaddr or HTTP URL.
+    InvalidEndpoint(String),
+    /// A header, credential or certificate in `ClientOptions` could not be
+    /// used.
+    InvalidConfig(String),
+    /// A multihash uses a hash function this crate cannot compute.
+    UnsupportedHash(u64),
+    /// A `HashFunction` was given a digest size it does not have, such as
+    /// `Blake2b(65)`.
+    InvalidHashFunction(String),
+    /// Content did not hash to the multihash it was fetched by.
+    HashMismatch(Multihash),
+    /// Content that was expected to be text was not valid UTF-8.
+    Utf8(Utf8Error),
+    /// Reading a response body failed.
+    Io(io::Error),
+    /// The path given to a pin command is not pinned.
+    NotPinned(String),
+    /// A stream was stopped through its `CancelHandle`.
+    Cancelled,
+    NoSuchHash
+}
+
+impl IPFSError {
+    /// Builds the error for a non-success response from its status and body.
+    pub fn from_response(status: StatusCode, body: String) -> IPFSError {
+        match ApiError::from_body(status, &body) {
+            Some(api) => IPFSError::Api(api),
+            None => IPFSError::Http(status, body)
+        }
+    }
+}
+
+impl fmt::Display for IPFSError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IPFSError::ConnectionError(ref err) => write!(f, "connection error: {}", err),
+            IPFSError::Http(status, ref body) if body.is_empty() => write!(f, "daemon returned {}", status),
+            IPFSError::Http(status, ref body) => write!(f, "daemon returned {}: {}", status, body),
+            IPFSError::Api(ref err) => write!(f, "daemon error: {}", err),
+            IPFSError::Protobuf(ref err) => write!(f, "protobuf decode error: {}", err),
+            IPFSError::Json(ref err) => write!(f, "JSON decode error: {}", err),
+            IPFSError::UnexpectedResponse(ref what) => write!(f, "unexpected response: {}", what),
+            IPFSError::InvalidBlock(ref what) => write!(f, "invalid block: {}", what),
+            IPFSError::InvalidCid(ref cid) => write!(f, "invalid CID {:?}", cid),
+            IPFSError::InvalidMultibase(ref s) => write!(f, "invalid multibase string {:?}", s),
+            IPFSError::InvalidMultihash(ref bytes) => write!(f, "invalid multihash {:?}", bytes),
+            IPFSError::InvalidChunker(ref spec) => write!(f, "invalid chunker {:?}", spec),
+            IPFSError::InvalidEndpoint(ref addr) => write!(f, "invalid API endpoint {:?}", addr),
+            IPFSError::InvalidConfig(ref what) => write!(f, "invalid client options: {}", what),
+            IPFSError::UnsupportedHash(code) => write!(f, "unsupported hash function 0x{:x}", code),
+            IPFSError::InvalidHashFunction(ref name) => write!(f, "invalid hash function {}", name),
+            IPFSError::HashMismatch(ref hash) => write!(f, "content does not match {:?}", hash),
+            IPFSError::Utf8(ref err) => write!(f, "invalid UTF-8: {}", err),
+            IPFSError::Io(ref err) => write!(f, "I/O error: {}", err),
+            IPFSError::NotPinned(ref path) => write!(f, "{} is not pinned", path),
+            IPFSError::Cancelled => f.write_str("cancelled"),
+            IPFSError::NoSuchHash => f.write_str("no such hash")
+        }
+    }
+}
+
+impl Error for IPFSError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            IPFSError::ConnectionError(ref err) => Some(&**err),
+            IPFSError::Protobuf(ref err) => Some(err),
+            IPFSError::Json(ref err) => Some(err),
+            IPFSError::Utf8(ref err) => Some(err),
+            IPFSError::Io(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl From<reqwest::Error> for IPFSError {
+    fn from(err: reqwest::Error) -> IPFSError {
+        IPFSError::ConnectionError(Box::new(err))
+    }
+}
+
+impl From<ProtobufError> for IPFSError {
+    fn from(err: ProtobufError) -> IPFSError {
+        IPFSError::Protobuf(err)
+    }
+}
+
+impl From<serde_json::Error> for IPFSError {
+    fn from(err: serde_json::Error) -> IPFSError {
+        IPFSError::Json(err)
+    }
+}
+
+impl From<Utf8Error> for IPFSError {
+    fn from(err: Utf8Error) -> IPFSError {
+        IPFSError::Utf8(err)
+    }
+}
+
+impl From<io::Error> for IPFSError {
+    fn from(err: io::Error) -> IPFSError {
+        IPFSError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn daemon_error_body_is_parsed() {
+        let body = r#"{"Message":"invalid path \"foo\"","Code":0,"Type":"error"}"#;
+        match IPFSError::from_response(StatusCode::INTERNAL_SERVER_ERROR, body.to_string()) {
+            IPFSError::Api(err) => {
+                assert_eq!(err.status, StatusCode::INTERNAL_SERVER_ERROR);
+                assert_eq!(err.message, "invalid path \"foo\"");
+                assert_eq!(err.code, 0);
+                assert_eq!(err.kind, "error");
+            }
+            other => panic!("unexpected error {:?}", other)
+        }
+    }
+
+    #[test]
+    fn plain_error_body_is_kept() {
+        match IPFSError::from_response(StatusCode::NOT_FOUND, "404 page not found".to_string()) {
+            IPFSError::Http(StatusCode::NOT_FOUND, body) => assert_eq!(body, "404 page not found"),
+            other => panic!("unexpected error {:?}", other)
+        }
+    }
+}
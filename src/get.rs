@@ -0,0 +1,285 @@
+//! `ipfs get`: downloads a file or directory tree through `/api/v0/get`,
+//! which streams it as a tar archive, and either writes the archive out or
+//! recreates the tree on the local filesystem.
+
+#[cfg(feature = "blocking")]
+use attrs::{FileAttrs, Mtime};
+#[cfg(feature = "blocking")]
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use std::fs::{self, File};
+#[cfg(feature = "blocking")]
+use std::io::{self, Read};
+#[cfg(feature = "blocking")]
+use std::path::{Component, Path, PathBuf};
+#[cfg(feature = "blocking")]
+use tar;
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// Archive formats `get_to_path` can save instead of extracting, and that
+/// `AsyncIPFS::get` streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Archive {
+    Tar,
+    /// A gzip-compressed tar archive at the given compression level, 1 to 9.
+    TarGzip(u32)
+}
+
+/// Options for `get_to_path` and `AsyncIPFS::get`. Only the archive format
+/// matters to the latter, which leaves extraction to the caller.
+#[derive(Debug, Clone, Default)]
+pub struct GetOptions {
+    archive: Option<Archive>,
+    preserve_mode: bool,
+    preserve_mtime: bool
+}
+
+impl GetOptions {
+    pub fn new() -> GetOptions {
+        GetOptions::default()
+    }
+
+    /// Save the tree as an archive at the destination rather than extracting
+    /// it.
+    pub fn archive(mut self, archive: Archive) -> GetOptions {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// Restore the permission bits recorded for each entry. Otherwise files
+    /// get the process's defaults.
+    pub fn preserve_mode(mut self, preserve: bool) -> GetOptions {
+        self.preserve_mode = preserve;
+        self
+    }
+
+    /// Restore the modification time recorded for each entry.
+    pub fn preserve_mtime(mut self, preserve: bool) -> GetOptions {
+        self.preserve_mtime = preserve;
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        match self.archive {
+            None => Vec::new(),
+            Some(Archive::Tar) => vec![("archive", "true".to_string())],
+            Some(Archive::TarGzip(level)) => vec![("archive", "true".to_string()), ("compress", "true".to_string()),
+                                                  ("compression-level", level.to_string())]
+        }
+    }
+}
+
+/// How much of a `get` has arrived. `total` is the size of the content as
+/// the daemon announced it, which an archive exceeds by its headers.
+#[cfg(feature = "blocking")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GetProgress {
+    pub bytes: u64,
+    pub total: Option<u64>
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Downloads the file or directory at `path` to `dest`, which becomes the
+    /// root of the tree, as with `ipfs get -o <dest>`.
+    pub fn get_to_path(&self, path: &str, dest: &Path, options: &GetOptions) -> Result<(), IPFSError> {
+        self.get_to_path_with_progress(path, dest, options, |_| ())
+    }
+
+    /// Like `get_to_path`, calling `progress` as the download proceeds.
+    pub fn get_to_path_with_progress<F>(&self, path: &str, dest: &Path, options: &GetOptions, progress: F)
+                                        -> Result<(), IPFSError>
+        where F: FnMut(GetProgress)
+    {
+        let mut args = vec![("arg", self.resolve_path(path)?)];
+        args.extend(options.to_args());
+        let res = self.request("get", &args)?;
+        let total = res.headers().get("X-Content-Length")
+                                 .and_then(|value| value.to_str().ok())
+                                 .and_then(|value| value.parse().ok());
+        let mut body = Progress { inner: res, bytes: 0, total, callback: progress };
+        if options.archive.is_some() {
+            io::copy(&mut body, &mut File::create(dest)?)?;
+            return Ok(());
+        }
+        unpack(body, dest, options)
+    }
+}
+
+#[cfg(feature = "blocking")]
+struct Progress<R, F> {
+    inner: R,
+    bytes: u64,
+    total: Option<u64>,
+    callback: F
+}
+
+#[cfg(feature = "blocking")]
+impl<R: Read, F: FnMut(GetProgress)> Read for Progress<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.bytes += n as u64;
+            (self.callback)(GetProgress { bytes: self.bytes, total: self.total });
+        }
+        Ok(n)
+    }
+}
+
+/// Extracts the tar stream the daemon sends, with its top-level entry
+/// renamed to `dest`. Directory metadata is applied last, once nothing more
+/// will be written into them and a read-only mode can no longer get in the
+/// way.
+#[cfg(feature = "blocking")]
+fn unpack<R: Read>(tarball: R, dest: &Path, options: &GetOptions) -> Result<(), IPFSError> {
+    let mut archive = tar::Archive::new(tarball);
+    let mut directories = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let target = match local_path(dest, &entry.path()?) {
+            Some(ref target) if !through_symlink(dest, target) => target.clone(),
+            _ => return Err(IPFSError::UnexpectedResponse(format!("unsafe path {:?} in archive",
+                                                                  entry.path()?)))
+        };
+        let header = entry.header();
+        // The daemon never sends hard links, and `unpack` would resolve one's
+        // target outside `dest`.
+        if header.entry_type().is_hard_link() {
+            return Err(IPFSError::UnexpectedResponse(format!("hard link {:?} in archive", entry.path()?)));
+        }
+        let attrs = FileAttrs {
+            mode: if options.preserve_mode { Some(header.mode()?) } else { None },
+            mtime: if options.preserve_mtime { Some(Mtime::new(header.mtime()? as i64, 0)) } else { None }
+        };
+        if header.entry_type().is_dir() {
+            fs::create_dir_all(&target)?;
+            directories.push((target, attrs));
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        entry.set_preserve_permissions(options.preserve_mode);
+        entry.set_preserve_mtime(options.preserve_mtime);
+        entry.unpack(&target)?;
+    }
+    for (directory, attrs) in directories.iter().rev() {
+        attrs.apply(directory)?;
+    }
+    Ok(())
+}
+
+/// Where an archive entry goes: its path with the first component replaced
+/// by `dest`. Paths that could escape `dest` give `None`.
+#[cfg(feature = "blocking")]
+fn local_path(dest: &Path, entry: &Path) -> Option<PathBuf> {
+    let mut components = entry.components();
+    match components.next() {
+        Some(Component::Normal(_)) => {}
+        _ => return None
+    }
+    let mut target = dest.to_path_buf();
+    for component in components {
+        match component {
+            Component::Normal(part) => target.push(part),
+            Component::CurDir => {}
+            _ => return None
+        }
+    }
+    Some(target)
+}
+
+/// Whether writing `target` would go through a symlink below `dest`, such
+/// as one an earlier entry made: an archive holding `QmX/evil -> /etc` and
+/// then `QmX/evil/passwd` must not reach `/etc/passwd`. `target` itself may
+/// be a symlink, since unpacking replaces it rather than following it.
+#[cfg(feature = "blocking")]
+fn through_symlink(dest: &Path, target: &Path) -> bool {
+    let relative = match target.strip_prefix(dest) {
+        Ok(relative) => relative,
+        Err(_) => return true
+    };
+    let mut path = dest.to_path_buf();
+    let mut components = relative.components().peekable();
+    while let Some(component) = components.next() {
+        if components.peek().is_none() {
+            break;
+        }
+        path.push(component);
+        if fs::symlink_metadata(&path).map(|metadata| metadata.file_type().is_symlink()).unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+
+    use super::*;
+    use std::env;
+    use tar::{Builder, EntryType, Header};
+
+    fn header(kind: EntryType, size: u64, mode: u32) -> Header {
+        let mut header = Header::new_ustar();
+        header.set_entry_type(kind);
+        header.set_size(size);
+        header.set_mode(mode);
+        header.set_mtime(1_700_000_000);
+        header
+    }
+
+    #[test]
+    fn trees_are_extracted_under_dest() {
+        let mut builder = Builder::new(Vec::new());
+        builder.append_data(&mut header(EntryType::Directory, 0, 0o750), "QmSite", &b""[..]).unwrap();
+        builder.append_data(&mut header(EntryType::Regular, 5, 0o600), "QmSite/docs/a.txt", &b"hello"[..]).unwrap();
+        let mut link = header(EntryType::Symlink, 0, 0o777);
+        builder.append_link(&mut link, "QmSite/latest", "docs/a.txt").unwrap();
+        let tarball = builder.into_inner().unwrap();
+
+        let dest = env::temp_dir().join(format!("ipfs-api-get-{}", ::std::process::id()));
+        let _ = fs::remove_dir_all(&dest);
+        unpack(&tarball[..], &dest, &GetOptions::new().preserve_mode(true).preserve_mtime(true)).unwrap();
+
+        let mut content = String::new();
+        File::open(dest.join("latest")).unwrap().read_to_string(&mut content).unwrap();
+        assert_eq!(content, "hello");
+        let modified = fs::metadata(&dest).unwrap().modified().unwrap();
+        assert_eq!(Mtime::from_system_time(modified), Mtime::new(1_700_000_000, 0));
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn symlinks_cannot_be_written_through() {
+        let root = env::temp_dir().join(format!("ipfs-api-get-evil-{}", ::std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let outside = root.join("outside");
+        fs::create_dir_all(&outside).unwrap();
+
+        let mut builder = Builder::new(Vec::new());
+        builder.append_data(&mut header(EntryType::Directory, 0, 0o755), "QmX", &b""[..]).unwrap();
+        let mut link = header(EntryType::Symlink, 0, 0o777);
+        builder.append_link(&mut link, "QmX/evil", &outside).unwrap();
+        builder.append_data(&mut header(EntryType::Regular, 4, 0o644), "QmX/evil/passwd", &b"root"[..]).unwrap();
+        let tarball = builder.into_inner().unwrap();
+
+        match unpack(&tarball[..], &root.join("dest"), &GetOptions::new()) {
+            Err(IPFSError::UnexpectedResponse(_)) => {}
+            other => panic!("unexpected result {:?}", other)
+        }
+        assert!(!outside.join("passwd").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn paths_cannot_escape_dest() {
+        let dest = Path::new("/srv/site");
+        assert_eq!(local_path(dest, Path::new("QmSite/a/./b")), Some(PathBuf::from("/srv/site/a/b")));
+        assert_eq!(local_path(dest, Path::new("QmSite")), Some(PathBuf::from("/srv/site")));
+        assert_eq!(local_path(dest, Path::new("QmSite/../../etc/passwd")), None);
+        assert_eq!(local_path(dest, Path::new("/etc/passwd")), None);
+    }
+}
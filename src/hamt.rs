@@ -0,0 +1,497 @@
+//! HAMT-sharded UnixFS directories. Directories too large for one block are
+//! spread over a hash array mapped trie: each `HAMTShard` node has `fanout`
+//! slots, an entry goes in the slot picked by the next bits of the murmur3
+//! hash of its name, and slots that more than one entry hashes to hold a
+//! child shard.
+//!
+//! Links from a shard are named with the slot number in upper-case hex,
+//! padded to a fixed width; a link to an entry appends the entry's name,
+//! while a link to a child shard is the bare slot number.
+
+use blocks::BlockSource;
+use cid::{Cid, Codec, Version};
+use dagpb::{decode_dag_pb, DagPbBuilder};
+use error::IPFSError;
+use multihash::HashFunction;
+use protobuf::Message;
+use std::collections::BTreeMap;
+use unixfs::{Data, Data_DataType};
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// The multicodec of murmur3-x64-64, the only hash go-unixfs shards with.
+const MURMUR3_X64_64: u64 = 0x22;
+const DEFAULT_FANOUT: usize = 256;
+
+/// Encoded shards with their CIDs.
+type Blocks = Vec<(Cid, Vec<u8>)>;
+
+/// One entry of a directory: a named link to a file, directory or symlink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    pub name: String,
+    pub cid: Cid,
+    /// Cumulative size of the target, the `Tsize` it is linked with.
+    pub tsize: u64
+}
+
+/// Reads a sharded directory in place, fetching only the shards needed.
+pub struct ShardedDirectory<S: BlockSource> {
+    source: S,
+    root: Shard
+}
+
+impl<S: BlockSource> ShardedDirectory<S> {
+    pub fn new(source: S, root: &Cid) -> Result<ShardedDirectory<S>, IPFSError> {
+        let root = Shard::fetch(&source, root, None)?;
+        Ok(ShardedDirectory { source, root })
+    }
+
+    pub fn fanout(&self) -> usize {
+        self.root.fanout
+    }
+
+    /// Looks up an entry by name, following its hash down the trie.
+    pub fn get(&self, name: &str) -> Result<Option<DirEntry>, IPFSError> {
+        let hash = murmur3_x64_64(name.as_bytes());
+        let mut shard = self.root.clone();
+        let mut depth = 0;
+        loop {
+            let slot = slot_at(hash, depth, shard.fanout)?;
+            match shard.slots.remove(&slot) {
+                Some(Slot::Entry(entry)) => return Ok(if entry.name == name { Some(entry) } else { None }),
+                Some(Slot::Link(cid, _)) => shard = Shard::fetch(&self.source, &cid, Some(shard.fanout))?,
+                Some(Slot::Shard(_)) => unreachable!("fetched shards hold links, not loaded children"),
+                None => return Ok(None)
+            }
+            depth += 1;
+        }
+    }
+
+    /// Every entry in the directory, in trie order.
+    pub fn entries(&self) -> Result<Vec<DirEntry>, IPFSError> {
+        let mut entries = Vec::new();
+        self.collect(&self.root, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn collect(&self, shard: &Shard, entries: &mut Vec<DirEntry>) -> Result<(), IPFSError> {
+        for slot in shard.slots.values() {
+            match *slot {
+                Slot::Entry(ref entry) => entries.push(entry.clone()),
+                Slot::Link(ref cid, _) => self.collect(&Shard::fetch(&self.source, cid, Some(shard.fanout))?, entries)?,
+                Slot::Shard(ref child) => self.collect(child, entries)?
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Opens the sharded directory `cid` for reading block by block.
+    pub fn open_sharded_directory(&self, cid: &Cid) -> Result<ShardedDirectory<&IPFS>, IPFSError> {
+        ShardedDirectory::new(self, cid)
+    }
+}
+
+/// Builds or edits a sharded directory in memory. The layout depends only on
+/// the set of entries, not on the order they were added or removed in, so
+/// the resulting CID matches what go-ipfs produces for the same directory.
+#[derive(Debug, Clone)]
+pub struct ShardWriter {
+    root: Shard
+}
+
+impl Default for ShardWriter {
+    fn default() -> ShardWriter {
+        ShardWriter { root: Shard::new(DEFAULT_FANOUT) }
+    }
+}
+
+impl ShardWriter {
+    /// An empty directory with go-ipfs' fanout of 256.
+    pub fn new() -> ShardWriter {
+        ShardWriter::default()
+    }
+
+    /// An empty directory with another fanout, which must be a power of two
+    /// and a multiple of 8.
+    pub fn with_fanout(fanout: usize) -> Result<ShardWriter, IPFSError> {
+        check_fanout(fanout as u64)?;
+        Ok(ShardWriter { root: Shard::new(fanout) })
+    }
+
+    /// Loads every shard of an existing directory so it can be edited.
+    pub fn load<S: BlockSource>(source: &S, root: &Cid) -> Result<ShardWriter, IPFSError> {
+        let mut root = Shard::fetch(source, root, None)?;
+        root.load_children(source)?;
+        Ok(ShardWriter { root })
+    }
+
+    /// Adds `entry`, replacing any entry with the same name.
+    pub fn insert(&mut self, entry: DirEntry) -> Result<(), IPFSError> {
+        let hash = murmur3_x64_64(entry.name.as_bytes());
+        self.root.insert(entry, hash, 0)
+    }
+
+    /// Removes the entry called `name`, returning it if there was one.
+    pub fn remove(&mut self, name: &str) -> Result<Option<DirEntry>, IPFSError> {
+        let hash = murmur3_x64_64(name.as_bytes());
+        self.root.remove(name, hash, 0)
+    }
+
+    /// Encodes every shard as dag-pb, returning the root CID and the blocks,
+    /// child shards before their parents.
+    pub fn encode(&self, version: Version) -> Result<(Cid, Blocks), IPFSError> {
+        let mut blocks = Vec::new();
+        let (cid, _) = self.root.encode(version, &mut blocks)?;
+        Ok((cid, blocks))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Slot {
+    Entry(DirEntry),
+    /// A child shard that has not been fetched, with its `Tsize`.
+    Link(Cid, u64),
+    Shard(Box<Shard>)
+}
+
+#[derive(Debug, Clone)]
+struct Shard {
+    fanout: usize,
+    slots: BTreeMap<usize, Slot>
+}
+
+impl Shard {
+    fn new(fanout: usize) -> Shard {
+        Shard { fanout, slots: BTreeMap::new() }
+    }
+
+    /// Fetches and decodes a shard. Child shards must have their parent's
+    /// fanout.
+    fn fetch<S: BlockSource>(source: &S, cid: &Cid, fanout: Option<usize>) -> Result<Shard, IPFSError> {
+        let block = source.get_block(cid)?;
+        let mut node = decode_dag_pb(&block)?;
+        let mut content = Data::new();
+        content.merge_from_bytes(node.get_Data())?;
+        if content.get_Type() != Data_DataType::HAMTShard {
+            return Err(IPFSError::InvalidBlock(format!("{} is a {:?} node, not a HAMT shard", cid, content.get_Type())));
+        }
+        if content.get_hashType() != MURMUR3_X64_64 {
+            return Err(IPFSError::UnsupportedHash(content.get_hashType()));
+        }
+        check_fanout(content.get_fanout())?;
+        let shard_fanout = content.get_fanout() as usize;
+        if fanout.is_some_and(|fanout| fanout != shard_fanout) {
+            return Err(IPFSError::InvalidBlock(format!("shard {} has fanout {} under a parent of fanout {}",
+                                                       cid, shard_fanout, fanout.unwrap_or(0))));
+        }
+
+        let width = prefix_width(shard_fanout);
+        let bitfield = content.get_Data();
+        let mut shard = Shard::new(shard_fanout);
+        for mut link in node.take_Links().into_iter() {
+            let name = link.take_Name();
+            let slot = match name.get(..width).and_then(|prefix| usize::from_str_radix(prefix, 16).ok()) {
+                Some(slot) if slot < shard_fanout && prefix_of(slot, shard_fanout) == name[..width] => slot,
+                _ => return Err(IPFSError::InvalidBlock(format!("bad shard link name {:?} in {}", name, cid)))
+            };
+            if !bit_is_set(bitfield, slot) {
+                return Err(IPFSError::InvalidBlock(format!("slot {} of {} is linked but not in its bitfield", slot, cid)));
+            }
+            let target = Cid::from_bytes(link.get_Hash())?;
+            let value = if name.len() == width {
+                Slot::Link(target, link.get_Tsize())
+            } else {
+                Slot::Entry(DirEntry { name: name[width..].to_string(), cid: target, tsize: link.get_Tsize() })
+            };
+            if shard.slots.insert(slot, value).is_some() {
+                return Err(IPFSError::InvalidBlock(format!("slot {} of {} is linked twice", slot, cid)));
+            }
+        }
+        if count_bits(bitfield) != shard.slots.len() {
+            return Err(IPFSError::InvalidBlock(format!("bitfield of {} does not match its links", cid)));
+        }
+        Ok(shard)
+    }
+
+    fn load_children<S: BlockSource>(&mut self, source: &S) -> Result<(), IPFSError> {
+        let fanout = self.fanout;
+        for slot in self.slots.values_mut() {
+            let loaded = match *slot {
+                Slot::Link(ref cid, _) => {
+                    let mut child = Shard::fetch(source, cid, Some(fanout))?;
+                    child.load_children(source)?;
+                    child
+                }
+                _ => continue
+            };
+            *slot = Slot::Shard(Box::new(loaded));
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, entry: DirEntry, hash: u64, depth: usize) -> Result<(), IPFSError> {
+        let slot = slot_at(hash, depth, self.fanout)?;
+        let existing = match self.slots.remove(&slot) {
+            None => {
+                self.slots.insert(slot, Slot::Entry(entry));
+                return Ok(());
+            }
+            Some(Slot::Entry(ref existing)) if existing.name == entry.name => {
+                self.slots.insert(slot, Slot::Entry(entry));
+                return Ok(());
+            }
+            Some(Slot::Shard(mut child)) => {
+                let result = child.insert(entry, hash, depth + 1);
+                self.slots.insert(slot, Slot::Shard(child));
+                return result;
+            }
+            Some(Slot::Link(..)) => unreachable!("writers load every shard"),
+            Some(Slot::Entry(existing)) => existing
+        };
+
+        // Two names share this slot, so it becomes a shard holding both.
+        let mut child = Shard::new(self.fanout);
+        let existing_hash = murmur3_x64_64(existing.name.as_bytes());
+        child.insert(existing, existing_hash, depth + 1)?;
+        child.insert(entry, hash, depth + 1)?;
+        self.slots.insert(slot, Slot::Shard(Box::new(child)));
+        Ok(())
+    }
+
+    /// Removes `name`, collapsing a child shard into its parent's slot once
+    /// it holds a single entry, as go-unixfs does.
+    fn remove(&mut self, name: &str, hash: u64, depth: usize) -> Result<Option<DirEntry>, IPFSError> {
+        let slot = slot_at(hash, depth, self.fanout)?;
+        match self.slots.remove(&slot) {
+            None => Ok(None),
+            Some(Slot::Entry(entry)) => {
+                if entry.name == name {
+                    return Ok(Some(entry));
+                }
+                self.slots.insert(slot, Slot::Entry(entry));
+                Ok(None)
+            }
+            Some(Slot::Shard(mut child)) => {
+                let removed = child.remove(name, hash, depth + 1)?;
+                let collapse = child.slots.len() == 1 && matches!(child.slots.values().next(), Some(&Slot::Entry(_)));
+                if collapse {
+                    let only = child.slots.into_iter().next().map(|(_, only)| only).expect("one slot");
+                    self.slots.insert(slot, only);
+                } else if !child.slots.is_empty() {
+                    self.slots.insert(slot, Slot::Shard(child));
+                }
+                Ok(removed)
+            }
+            Some(Slot::Link(..)) => unreachable!("writers load every shard")
+        }
+    }
+
+    /// Encodes this shard and its children, returning its CID and `Tsize`.
+    fn encode(&self, version: Version, blocks: &mut Blocks) -> Result<(Cid, u64), IPFSError> {
+        let mut bitfield = vec![0u8; self.fanout / 8];
+        let mut builder = DagPbBuilder::new();
+        let mut links_size = 0;
+        for (&slot, value) in &self.slots {
+            set_bit(&mut bitfield, slot);
+            let prefix = prefix_of(slot, self.fanout);
+            let (name, cid, tsize) = match *value {
+                Slot::Entry(ref entry) => (format!("{}{}", prefix, entry.name), entry.cid.clone(), entry.tsize),
+                Slot::Link(ref cid, tsize) => (prefix, cid.clone(), tsize),
+                Slot::Shard(ref child) => {
+                    let (cid, tsize) = child.encode(version, blocks)?;
+                    (prefix, cid, tsize)
+                }
+            };
+            builder = builder.link(&name, &cid, tsize);
+            links_size += tsize;
+        }
+
+        let mut content = Data::new();
+        content.set_Type(Data_DataType::HAMTShard);
+        // The bitfield is written as a big-endian integer with leading zero
+        // bytes dropped.
+        let first = bitfield.iter().position(|&b| b != 0).unwrap_or(bitfield.len());
+        if first < bitfield.len() {
+            content.set_Data(bitfield[first..].to_vec());
+        }
+        content.set_hashType(MURMUR3_X64_64);
+        content.set_fanout(self.fanout as u64);
+
+        let block = builder.data(content.write_to_bytes()?).encode();
+        let mut cid = Cid::for_block(Codec::DagPb, HashFunction::Sha2_256, &block)?;
+        if version == Version::V0 {
+            cid = cid.to_v0()?;
+        }
+        let tsize = block.len() as u64 + links_size;
+        blocks.push((cid.clone(), block));
+        Ok((cid, tsize))
+    }
+}
+
+fn check_fanout(fanout: u64) -> Result<(), IPFSError> {
+    if fanout < 8 || !fanout.is_power_of_two() || fanout > 1 << 16 {
+        return Err(IPFSError::InvalidBlock(format!("unsupported HAMT fanout {}", fanout)));
+    }
+    Ok(())
+}
+
+/// Hex digits needed for the largest slot number.
+fn prefix_width(fanout: usize) -> usize {
+    format!("{:X}", fanout - 1).len()
+}
+
+fn prefix_of(slot: usize, fanout: usize) -> String {
+    format!("{:01$X}", slot, prefix_width(fanout))
+}
+
+/// The slot an entry falls in at `depth`: the next log2(fanout) bits of its
+/// hash, most significant first.
+fn slot_at(hash: u64, depth: usize, fanout: usize) -> Result<usize, IPFSError> {
+    let bits = fanout.trailing_zeros() as usize;
+    if (depth + 1) * bits > 64 {
+        return Err(IPFSError::InvalidBlock("HAMT is deeper than the hash is long".to_string()));
+    }
+    Ok(((hash >> (64 - (depth + 1) * bits)) as usize) & (fanout - 1))
+}
+
+/// Bit `i` of the bitfield is worth 2^i in its big-endian form.
+fn bit_is_set(bitfield: &[u8], i: usize) -> bool {
+    let byte = i / 8;
+    byte < bitfield.len() && bitfield[bitfield.len() - 1 - byte] & (1 << (i % 8)) != 0
+}
+
+fn set_bit(bitfield: &mut [u8], i: usize) {
+    let len = bitfield.len();
+    bitfield[len - 1 - i / 8] |= 1 << (i % 8);
+}
+
+fn count_bits(bitfield: &[u8]) -> usize {
+    bitfield.iter().map(|b| b.count_ones() as usize).sum()
+}
+
+/// The first 64 bits of MurmurHash3 x64 128 with seed 0, read big-endian,
+/// which is what go-unixfs means by murmur3-x64-64.
+fn murmur3_x64_64(data: &[u8]) -> u64 {
+    const C1: u64 = 0x87c37b91114253d5;
+    const C2: u64 = 0x4cf5ad432745937f;
+    let (mut h1, mut h2) = (0u64, 0u64);
+
+    let blocks = data.len() / 16;
+    for i in 0..blocks {
+        let k1 = le_u64(&data[i * 16..i * 16 + 8]);
+        let k2 = le_u64(&data[i * 16 + 8..i * 16 + 16]);
+        h1 ^= k1.wrapping_mul(C1).rotate_left(31).wrapping_mul(C2);
+        h1 = h1.rotate_left(27).wrapping_add(h2).wrapping_mul(5).wrapping_add(0x52dce729);
+        h2 ^= k2.wrapping_mul(C2).rotate_left(33).wrapping_mul(C1);
+        h2 = h2.rotate_left(31).wrapping_add(h1).wrapping_mul(5).wrapping_add(0x38495ab5);
+    }
+
+    let tail = &data[blocks * 16..];
+    if tail.len() > 8 {
+        h2 ^= le_u64(&tail[8..]).wrapping_mul(C2).rotate_left(33).wrapping_mul(C1);
+    }
+    if !tail.is_empty() {
+        h1 ^= le_u64(&tail[..tail.len().min(8)]).wrapping_mul(C1).rotate_left(31).wrapping_mul(C2);
+    }
+
+    h1 ^= data.len() as u64;
+    h2 ^= data.len() as u64;
+    h1 = h1.wrapping_add(h2);
+    h2 = h2.wrapping_add(h1);
+    h1 = fmix64(h1);
+    h2 = fmix64(h2);
+    h1.wrapping_add(h2)
+}
+
+/// Reads up to 8 bytes as a little-endian integer.
+fn le_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0, |n, &b| n << 8 | b as u64)
+}
+
+fn fmix64(mut k: u64) -> u64 {
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xff51afd7ed558ccd);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xc4ceb9fe1a85ec53);
+    k ^ (k >> 33)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::collections::HashMap;
+
+    fn entry(i: usize) -> DirEntry {
+        DirEntry {
+            name: format!("file-{}.txt", i),
+            cid: "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".parse().unwrap(),
+            tsize: 23
+        }
+    }
+
+    fn store(writer: &ShardWriter) -> (Cid, HashMap<Cid, Vec<u8>>) {
+        let (root, blocks) = writer.encode(Version::V0).unwrap();
+        (root, blocks.into_iter().collect())
+    }
+
+    #[test]
+    fn murmur3_matches_reference_vectors() {
+        assert_eq!(murmur3_x64_64(b""), 0);
+        assert_eq!(murmur3_x64_64(b"hello"), 0xcbd8a7b341bd9b02);
+        assert_eq!(murmur3_x64_64(b"The quick brown fox jumps over the lazy dog"), 0xe34bbc7bbc071b6c);
+    }
+
+    #[test]
+    fn layout_does_not_depend_on_insertion_order() {
+        let mut forwards = ShardWriter::new();
+        let mut backwards = ShardWriter::new();
+        for i in 0..600 {
+            forwards.insert(entry(i)).unwrap();
+            backwards.insert(entry(599 - i)).unwrap();
+        }
+        assert_eq!(store(&forwards).0, store(&backwards).0);
+    }
+
+    #[test]
+    fn entries_can_be_found_and_listed() {
+        let mut writer = ShardWriter::new();
+        for i in 0..600 {
+            writer.insert(entry(i)).unwrap();
+        }
+        let (root, blocks) = store(&writer);
+        assert!(blocks.len() > 1);
+
+        let directory = ShardedDirectory::new(&blocks, &root).unwrap();
+        assert_eq!(directory.get("file-42.txt").unwrap(), Some(entry(42)));
+        assert_eq!(directory.get("missing").unwrap(), None);
+        let mut names: Vec<String> = directory.entries().unwrap().into_iter().map(|e| e.name).collect();
+        names.sort();
+        let mut expected: Vec<String> = (0..600).map(|i| entry(i).name).collect();
+        expected.sort();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn removing_entries_collapses_shards() {
+        let mut writer = ShardWriter::new();
+        for i in 0..600 {
+            writer.insert(entry(i)).unwrap();
+        }
+        let (root, blocks) = store(&writer);
+        let mut edited = ShardWriter::load(&blocks, &root).unwrap();
+        for i in 1..600 {
+            assert_eq!(edited.remove(&entry(i).name).unwrap(), Some(entry(i)));
+        }
+
+        let mut single = ShardWriter::new();
+        single.insert(entry(0)).unwrap();
+        let (root, blocks) = store(&edited);
+        assert_eq!(root, store(&single).0);
+        assert_eq!(blocks.len(), 1);
+    }
+}
@@ -0,0 +1,830 @@
+//! An offline UnixFS importer: chunks a file and builds its DAG the way
+//! `ipfs add` does, so a file's CID can be known without a daemon. The
+//! blocks it produces can later be stored with `block_put`, or read back
+//! with `FileReader` from a `HashMap<Cid, Vec<u8>>`.
+
+use attrs::{self, FileAttrs, Mtime};
+use cid::{Cid, Codec, Version};
+use dagpb::{decode_dag_pb, encode_dag_pb, DagPbBuilder};
+use error::IPFSError;
+use multihash::HashFunction;
+use protobuf::Message;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, ErrorKind, Read};
+use std::path::Path;
+use std::str::FromStr;
+use unixfs::{Data, Data_DataType};
+
+/// The daemon's default chunk size.
+const DEFAULT_CHUNK_SIZE: usize = 262144;
+/// The largest chunk the daemon accepts.
+const CHUNK_SIZE_LIMIT: usize = 1048576;
+/// Links per internal node, as in go-unixfs.
+const MAX_LINKS: usize = 174;
+/// How many subtrees of each depth the trickle layout adds to a node.
+const DEPTH_REPEAT: usize = 4;
+
+/// How a file is cut into leaves. Parses from, and formats as, the strings
+/// `ipfs add --chunker` takes: `size-<bytes>`, `rabin`, `rabin-<avg>`,
+/// `rabin-<min>-<avg>-<max>` or `buzhash`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chunker {
+    /// Fixed-size chunks.
+    Size(usize),
+    /// Content-defined chunks cut by a Rabin fingerprint over a sliding
+    /// window, as go-ipfs-chunker's `rabin` does.
+    Rabin { min: usize, avg: usize, max: usize },
+    /// Content-defined chunks of 128 KiB to 512 KiB cut by a cyclic
+    /// polynomial (buzhash) over a 32-byte window, as go-ipfs-chunker's
+    /// `buzhash` does. It has no parameters.
+    Buzhash
+}
+
+impl Default for Chunker {
+    fn default() -> Chunker {
+        Chunker::Size(DEFAULT_CHUNK_SIZE)
+    }
+}
+
+impl FromStr for Chunker {
+    type Err = IPFSError;
+
+    fn from_str(s: &str) -> Result<Chunker, IPFSError> {
+        let invalid = || IPFSError::InvalidChunker(s.to_string());
+        let mut parts = s.split('-');
+        let kind = parts.next().unwrap_or("");
+        let n = parts.map(|p| p.parse::<usize>()).collect::<Result<Vec<_>, _>>().map_err(|_| invalid())?;
+        let chunker = match (kind, n.len()) {
+            ("", 0) | ("default", 0) => Chunker::default(),
+            ("size", 1) => Chunker::Size(n[0]),
+            ("rabin", 0) => rabin_around(DEFAULT_CHUNK_SIZE),
+            ("rabin", 1) => rabin_around(n[0]),
+            ("rabin", 3) => Chunker::Rabin { min: n[0], avg: n[1], max: n[2] },
+            ("buzhash", 0) => Chunker::Buzhash,
+            _ => return Err(invalid())
+        };
+        let valid = match chunker {
+            Chunker::Size(size) => size > 0 && size <= CHUNK_SIZE_LIMIT,
+            Chunker::Rabin { min, avg, max } => min >= WINDOW_SIZE && min <= avg && avg <= max && max <= CHUNK_SIZE_LIMIT,
+            Chunker::Buzhash => true
+        };
+        if valid { Ok(chunker) } else { Err(invalid()) }
+    }
+}
+
+impl fmt::Display for Chunker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Chunker::Size(size) => write!(f, "size-{}", size),
+            Chunker::Rabin { min, avg, max } => write!(f, "rabin-{}-{}-{}", min, avg, max),
+            Chunker::Buzhash => f.write_str("buzhash")
+        }
+    }
+}
+
+/// The bounds go-ipfs-chunker uses when only the average is given.
+fn rabin_around(avg: usize) -> Chunker {
+    Chunker::Rabin { min: avg / 3, avg, max: avg + avg / 2 }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// Every leaf at the same depth; the default.
+    Balanced,
+    /// Leaves spread over subtrees of growing depth, so the start of the
+    /// file is reachable quickly. Suited to streaming.
+    Trickle
+}
+
+/// Options for `import_file`, mirroring those of `ipfs add`. The defaults
+/// produce the same CIDs as a plain `ipfs add --only-hash`.
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    chunker: Chunker,
+    layout: Layout,
+    raw_leaves: Option<bool>,
+    cid_version: Option<Version>,
+    hash: HashFunction,
+    mode: Option<u32>,
+    mtime: Option<Mtime>,
+    preserve_mode: bool,
+    preserve_mtime: bool
+}
+
+impl Default for ImportOptions {
+    fn default() -> ImportOptions {
+        ImportOptions {
+            chunker: Chunker::default(),
+            layout: Layout::Balanced,
+            raw_leaves: None,
+            cid_version: None,
+            hash: HashFunction::Sha2_256,
+            mode: None,
+            mtime: None,
+            preserve_mode: false,
+            preserve_mtime: false
+        }
+    }
+}
+
+impl ImportOptions {
+    pub fn new() -> ImportOptions {
+        ImportOptions::default()
+    }
+
+    pub fn chunker(mut self, chunker: Chunker) -> ImportOptions {
+        self.chunker = chunker;
+        self
+    }
+
+    pub fn layout(mut self, layout: Layout) -> ImportOptions {
+        self.layout = layout;
+        self
+    }
+
+    /// Store leaves as raw blocks rather than UnixFS nodes. Defaults to on
+    /// for CIDv1 and off for CIDv0, as in the daemon, and like the daemon is
+    /// turned off whenever a mode or mtime is recorded.
+    pub fn raw_leaves(mut self, raw_leaves: bool) -> ImportOptions {
+        self.raw_leaves = Some(raw_leaves);
+        self
+    }
+
+    /// Defaults to CIDv0, or CIDv1 if the hash function is not sha2-256.
+    pub fn cid_version(mut self, version: Version) -> ImportOptions {
+        self.cid_version = Some(version);
+        self
+    }
+
+    pub fn hash(mut self, hash: HashFunction) -> ImportOptions {
+        self.hash = hash;
+        self
+    }
+
+    /// Permission bits to record in the file's root node.
+    pub fn mode(mut self, mode: u32) -> ImportOptions {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Modification time to record in the file's root node.
+    pub fn mtime(mut self, mtime: Mtime) -> ImportOptions {
+        self.mtime = Some(mtime);
+        self
+    }
+
+    /// Have `import_path` record the local file's mode, unless `mode` is set.
+    pub fn preserve_mode(mut self, preserve: bool) -> ImportOptions {
+        self.preserve_mode = preserve;
+        self
+    }
+
+    /// Have `import_path` record the local file's modification time, unless
+    /// `mtime` is set.
+    pub fn preserve_mtime(mut self, preserve: bool) -> ImportOptions {
+        self.preserve_mtime = preserve;
+        self
+    }
+}
+
+/// The result of `import_file`.
+#[derive(Debug, Clone)]
+pub struct Imported {
+    pub root: Cid,
+    /// Cumulative size of the DAG, as `add` reports it.
+    pub size: u64,
+    /// Every distinct block of the DAG, children before their parents.
+    pub blocks: Vec<(Cid, Vec<u8>)>
+}
+
+/// Imports the local file at `path`, recording its metadata as the options'
+/// `preserve_mode` and `preserve_mtime` ask.
+pub fn import_path(path: &Path, options: &ImportOptions) -> Result<Imported, IPFSError> {
+    let local = FileAttrs::from_metadata(&fs::metadata(path)?);
+    let mut options = options.clone();
+    if options.preserve_mode && options.mode.is_none() {
+        options.mode = local.mode;
+    }
+    if options.preserve_mtime && options.mtime.is_none() {
+        options.mtime = local.mtime;
+    }
+    import_file(File::open(path)?, &options)
+}
+
+/// Chunks `data` and builds its UnixFS DAG without contacting a daemon.
+pub fn import_file<R: Read>(data: R, options: &ImportOptions) -> Result<Imported, IPFSError> {
+    let version = match options.cid_version {
+        Some(Version::V0) if options.hash != HashFunction::Sha2_256 => {
+            return Err(IPFSError::InvalidCid(format!("CIDv0 cannot use {}", options.hash.name())));
+        }
+        Some(version) => version,
+        None if options.hash != HashFunction::Sha2_256 => Version::V1,
+        None => Version::V0
+    };
+    let file_attrs = FileAttrs { mode: options.mode, mtime: options.mtime };
+    let mut builder = DagBuilder {
+        splitter: Splitter::new(data, options.chunker),
+        next: None,
+        version,
+        raw_leaves: options.raw_leaves.unwrap_or(version == Version::V1) && file_attrs.is_empty(),
+        hash: options.hash,
+        seen: HashSet::new(),
+        blocks: Vec::new()
+    };
+    builder.next = builder.splitter.next_chunk()?;
+
+    let root = match options.layout {
+        Layout::Balanced => builder.balanced()?,
+        Layout::Trickle => builder.trickle(None)?
+    };
+    let root = builder.record_attrs(root, &file_attrs)?;
+    Ok(Imported { root: root.cid, size: root.tsize, blocks: builder.blocks })
+}
+
+/// A node once it has been encoded: its CID, its cumulative size (the `Tsize`
+/// a parent links to it with) and the file bytes below it.
+struct Built {
+    cid: Cid,
+    tsize: u64,
+    filesize: u64
+}
+
+/// An internal file node still taking children.
+#[derive(Default)]
+struct Internal {
+    children: Vec<Built>
+}
+
+struct DagBuilder<R> {
+    splitter: Splitter<R>,
+    /// The chunk after the current one, read ahead so the layouts can tell
+    /// when the file is done.
+    next: Option<Vec<u8>>,
+    version: Version,
+    raw_leaves: bool,
+    hash: HashFunction,
+    seen: HashSet<Cid>,
+    blocks: Vec<(Cid, Vec<u8>)>
+}
+
+impl<R: Read> DagBuilder<R> {
+    fn done(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// go-unixfs' `balanced.Layout`: the first leaf is the root until a
+    /// second one arrives, after which the tree grows a level each time the
+    /// current one is full.
+    fn balanced(&mut self) -> Result<Built, IPFSError> {
+        if self.done() {
+            return self.leaf(None, Data_DataType::File);
+        }
+        let chunk = self.next_chunk()?;
+        let mut root = self.leaf(Some(chunk), Data_DataType::File)?;
+        let mut depth = 1;
+        while !self.done() {
+            let node = Internal { children: vec![root] };
+            root = self.fill_balanced(node, depth)?;
+            depth += 1;
+        }
+        Ok(root)
+    }
+
+    fn fill_balanced(&mut self, mut node: Internal, depth: usize) -> Result<Built, IPFSError> {
+        while node.children.len() < MAX_LINKS && !self.done() {
+            let child = if depth == 1 {
+                let chunk = self.next_chunk()?;
+                self.leaf(Some(chunk), Data_DataType::File)?
+            } else {
+                self.fill_balanced(Internal::default(), depth - 1)?
+            };
+            node.children.push(child);
+        }
+        self.commit(node)
+    }
+
+    /// go-unixfs' `trickle.Layout`: a layer of leaves, then `DEPTH_REPEAT`
+    /// subtrees of each depth below `max_depth`, or of ever growing depth
+    /// for the root. Trickle leaves have the UnixFS type `Raw`.
+    fn trickle(&mut self, max_depth: Option<usize>) -> Result<Built, IPFSError> {
+        let mut node = Internal::default();
+        while node.children.len() < MAX_LINKS && !self.done() {
+            let chunk = self.next_chunk()?;
+            node.children.push(self.leaf(Some(chunk), Data_DataType::Raw)?);
+        }
+        let mut depth = 1;
+        while max_depth.is_none_or(|max| depth < max) && !self.done() {
+            for _ in 0..DEPTH_REPEAT {
+                if self.done() {
+                    break;
+                }
+                node.children.push(self.trickle(Some(depth))?);
+            }
+            depth += 1;
+        }
+        self.commit(node)
+    }
+
+    fn next_chunk(&mut self) -> Result<Vec<u8>, IPFSError> {
+        let following = self.splitter.next_chunk()?;
+        Ok(::std::mem::replace(&mut self.next, following).expect("callers check done() first"))
+    }
+
+    /// A leaf holding `chunk`. Only an empty file has a leaf with no data.
+    fn leaf(&mut self, chunk: Option<Vec<u8>>, kind: Data_DataType) -> Result<Built, IPFSError> {
+        if self.raw_leaves {
+            let block = chunk.unwrap_or_default();
+            let size = block.len() as u64;
+            let cid = Cid::for_block(Codec::Raw, self.hash, &block)?;
+            self.store(cid.clone(), block);
+            return Ok(Built { cid, tsize: size, filesize: size });
+        }
+
+        let filesize = chunk.as_ref().map_or(0, |c| c.len() as u64);
+        let mut content = Data::new();
+        content.set_Type(kind);
+        if let Some(chunk) = chunk {
+            content.set_Data(chunk);
+        }
+        content.set_filesize(filesize);
+        let block = DagPbBuilder::new().data(content.write_to_bytes()?).encode();
+        self.store_node(block, 0, filesize)
+    }
+
+    fn commit(&mut self, node: Internal) -> Result<Built, IPFSError> {
+        let mut content = Data::new();
+        content.set_Type(Data_DataType::File);
+        content.set_filesize(node.children.iter().fold(0, |total, child| total + child.filesize));
+        content.set_blocksizes(node.children.iter().map(|child| child.filesize).collect());
+
+        let mut builder = DagPbBuilder::new();
+        let mut links_size = 0;
+        for child in &node.children {
+            builder = builder.link("", &child.cid, child.tsize);
+            links_size += child.tsize;
+        }
+        let filesize = content.get_filesize();
+        let block = builder.data(content.write_to_bytes()?).encode();
+        self.store_node(block, links_size, filesize)
+    }
+
+    /// Rewrites the root node with the file's metadata. Raw leaves are off
+    /// when there is metadata, so the root is always a UnixFS node.
+    fn record_attrs(&mut self, root: Built, file_attrs: &FileAttrs) -> Result<Built, IPFSError> {
+        if file_attrs.is_empty() {
+            return Ok(root);
+        }
+        let index = self.blocks.iter().rposition(|(cid, _)| *cid == root.cid).expect("the root was just stored");
+        let (_, block) = self.blocks.remove(index);
+        self.seen.remove(&root.cid);
+        let mut node = decode_dag_pb(&block)?;
+        let mut content = Data::new();
+        content.merge_from_bytes(node.get_Data())?;
+        attrs::encode(file_attrs, &mut content);
+        node.set_Data(content.write_to_bytes()?);
+        self.store_node(encode_dag_pb(&node)?, root.tsize - block.len() as u64, root.filesize)
+    }
+
+    fn store_node(&mut self, block: Vec<u8>, links_size: u64, filesize: u64) -> Result<Built, IPFSError> {
+        let mut cid = Cid::for_block(Codec::DagPb, self.hash, &block)?;
+        if self.version == Version::V0 {
+            cid = cid.to_v0()?;
+        }
+        let tsize = block.len() as u64 + links_size;
+        self.store(cid.clone(), block);
+        Ok(Built { cid, tsize, filesize })
+    }
+
+    fn store(&mut self, cid: Cid, block: Vec<u8>) {
+        if self.seen.insert(cid.clone()) {
+            self.blocks.push((cid, block));
+        }
+    }
+}
+
+/// Cuts a stream into chunks with the configured chunker.
+struct Splitter<R> {
+    reader: BufReader<R>,
+    chunker: Chunker,
+    rabin: Option<Rabin>,
+    /// Bytes read ahead of the next chunk by the buzhash chunker.
+    pending: Vec<u8>
+}
+
+impl<R: Read> Splitter<R> {
+    fn new(reader: R, chunker: Chunker) -> Splitter<R> {
+        let rabin = match chunker {
+            Chunker::Rabin { min, avg, max } => Some(Rabin::new(min, avg, max)),
+            Chunker::Size(_) | Chunker::Buzhash => None
+        };
+        Splitter { reader: BufReader::new(reader), chunker, rabin, pending: Vec::new() }
+    }
+
+    /// The next chunk, or `None` at the end of the stream. Chunks are never
+    /// empty.
+    fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, IPFSError> {
+        let mut chunk = Vec::new();
+        match self.chunker {
+            Chunker::Size(size) => {
+                // A short read does not end a chunk; only the end of the
+                // stream does.
+                while chunk.len() < size {
+                    let read = match (&mut self.reader).take((size - chunk.len()) as u64).read_to_end(&mut chunk) {
+                        Ok(read) => read,
+                        Err(ref err) if err.kind() == ErrorKind::Interrupted => continue,
+                        Err(err) => return Err(err.into())
+                    };
+                    if read == 0 {
+                        break;
+                    }
+                }
+            }
+            Chunker::Rabin { .. } => {
+                let rabin = self.rabin.as_mut().expect("rabin state is made with the splitter");
+                loop {
+                    let (cut, used) = {
+                        let buf = self.reader.fill_buf()?;
+                        if buf.is_empty() {
+                            break;
+                        }
+                        let mut used = 0;
+                        let mut cut = false;
+                        for &byte in buf {
+                            used += 1;
+                            chunk.push(byte);
+                            if rabin.roll(byte) {
+                                cut = true;
+                                break;
+                            }
+                        }
+                        (cut, used)
+                    };
+                    self.reader.consume(used);
+                    if cut {
+                        break;
+                    }
+                }
+                rabin.reset();
+            }
+            Chunker::Buzhash => {
+                // Like the Go code, look for a cut within the next BUZ_MAX
+                // bytes and keep whatever follows it for the next chunk.
+                chunk = ::std::mem::take(&mut self.pending);
+                (&mut self.reader).take((BUZ_MAX - chunk.len()) as u64).read_to_end(&mut chunk)?;
+                let cut = buzhash_cut(&chunk);
+                self.pending = chunk.split_off(cut);
+            }
+        }
+        Ok(if chunk.is_empty() { None } else { Some(chunk) })
+    }
+}
+
+/// The smallest buzhash chunk, other than the last one.
+const BUZ_MIN: usize = 128 << 10;
+/// The largest buzhash chunk.
+const BUZ_MAX: usize = 512 << 10;
+/// A chunk ends where the hash has all of these bits clear.
+const BUZ_MASK: u32 = (1 << 17) - 1;
+/// Bytes in the buzhash window.
+const BUZ_WINDOW: usize = 32;
+
+/// The length of the chunk at the start of `buf`, which holds `BUZ_MAX`
+/// bytes unless the stream ends sooner. This follows go-ipfs-chunker's
+/// `Buzhash.NextBytes` exactly, including where it starts hashing: the
+/// first window covers the `BUZ_WINDOW` bytes before `BUZ_MIN`.
+fn buzhash_cut(buf: &[u8]) -> usize {
+    if buf.len() < BUZ_MIN {
+        return buf.len();
+    }
+    let mut state = 0u32;
+    for &byte in &buf[BUZ_MIN - BUZ_WINDOW..BUZ_MIN] {
+        state = state.rotate_left(1) ^ BUZHASH_TABLE[byte as usize];
+    }
+    let mut i = BUZ_MIN - BUZ_WINDOW;
+    while i + BUZ_WINDOW < buf.len() && state & BUZ_MASK != 0 {
+        // The outgoing byte has been rotated a full 32 bits, back to where
+        // it started.
+        state = state.rotate_left(1) ^ BUZHASH_TABLE[buf[i] as usize] ^ BUZHASH_TABLE[buf[i + BUZ_WINDOW] as usize];
+        i += 1;
+    }
+    i + BUZ_WINDOW
+}
+
+/// go-ipfs-chunker's byte hashes for buzhash. Each bit position is set in
+/// exactly half of them.
+static BUZHASH_TABLE: [u32; 256] = [
+    0x6236e7d5, 0x10279b0b, 0x72818182, 0xdc526514, 0x2fd41e3d, 0x777ef8c8, 0x83ee5285, 0x2c8f3637,
+    0x2f049c1a, 0x57df9791, 0x9207151f, 0x9b544818, 0x74eef658, 0x2028ca60, 0x0271d91a, 0x27ae587e,
+    0xecf9fa5f, 0x236e71cd, 0xf43a8a2e, 0x0bb13380, 0x9e57912c, 0x89a26cdb, 0x9fcf3d71, 0xa86da6f1,
+    0x9c49f376, 0x346aecc7, 0xf094a9ee, 0xea99e9cb, 0xb01713c6, 0x088acffb, 0x2960a0fb, 0x344a626c,
+    0x7ff22a46, 0x6d7a1aa5, 0x6a714916, 0x41d454ca, 0x8325b830, 0x0b65f563, 0x447fecca, 0xf9d0ea5e,
+    0xc1d9d3d4, 0xcb5ec574, 0x55aae902, 0x86edc0e7, 0x0d3a9e33, 0xe70dc1e1, 0xe3c5f639, 0x9b43140a,
+    0xc6490ac5, 0x5e4030fb, 0x8e976dd5, 0xa87468ea, 0xf830ef6f, 0xcc1ed5a5, 0x611f4e78, 0xddd11905,
+    0xf2613904, 0x566c67b9, 0x905a5ccc, 0x7b37b3a4, 0x4b53898a, 0x6b8fd29d, 0xaad81575, 0x511be414,
+    0x3cfac1e7, 0x8029a179, 0xd40efeda, 0x07380e02, 0xdc9beffd, 0x2d049082, 0x99bc7831, 0xff5002a8,
+    0x21ce7646, 0x01cd049b, 0x0f43994f, 0xc3c6c5a5, 0xbbda5f50, 0x0ec15ec7, 0x9adb19b6, 0x0c1e80b9,
+    0xb9b52968, 0xae162419, 0x2542b405, 0x91a42e9d, 0x6be0f668, 0x6ed7a6b9, 0xbc2777b4, 0xe162ce56,
+    0x4266aad5, 0x60fdb704, 0x66f832a5, 0x9595f6ca, 0xfee83ced, 0x55228d99, 0x12bf0e28, 0x66896459,
+    0x0789afda, 0x0282baa8, 0x2367a343, 0x591491b0, 0x2ff1a4b1, 0x410739b6, 0x9b7055a0, 0x2e0eb229,
+    0x24fc8252, 0x3327d3df, 0xb0782669, 0x1c62e069, 0x7f503101, 0xf50593ae, 0xd9eb275d, 0xe00eb678,
+    0x5917ccde, 0x97b9660a, 0xdd06202d, 0xed229e22, 0xa9c735bf, 0xd6316fe6, 0x6fc72e4c, 0x0206dfa2,
+    0xd6b15c5a, 0x69d87b49, 0x09c97745, 0x13445d61, 0x35a975aa, 0x859aa9b9, 0x65380013, 0xd1fb6391,
+    0xc29255fd, 0x784a3b91, 0xb9e74c26, 0x63ce4d40, 0xc07cbe9e, 0xe6e4529e, 0x0fb3632f, 0x9438d9c9,
+    0x682f94a8, 0xf8fd4611, 0x257ec1ed, 0x475ce3d6, 0x60ee2db1, 0x2afab002, 0x2b9e4878, 0x86b340de,
+    0x1482fdca, 0xfe41b3bf, 0xd4a412b0, 0xe09db98c, 0xc1af5d53, 0x7e55e25f, 0xd3346b38, 0xb7a12cbd,
+    0x9c6827ba, 0x71f78bee, 0x8c3a0f52, 0x150491b0, 0xf26de912, 0x233e3a4e, 0xd309ebba, 0xa0a9e0ff,
+    0xca2b5921, 0xeeb9893c, 0x33829e88, 0x9870cc2a, 0x23c4b9d0, 0xeba32ea3, 0xbdac4d22, 0x3bc8c44c,
+    0x1e8d0397, 0xf9327735, 0x783b009f, 0x0eb83742, 0x2621dc71, 0xed017d03, 0x5c760aa1, 0x5a69814b,
+    0x96e3047f, 0xa93c9cde, 0x615c86f5, 0xb4322aa5, 0x4225534d, 0x0d2e2de3, 0xccfccc4b, 0x0bac2a57,
+    0xf0a06d04, 0xbc78d737, 0xf2d1f766, 0xf5a7953c, 0xbcdfda85, 0x5213b7d5, 0xbce8a328, 0xd38f5f18,
+    0xdb094244, 0xfe571253, 0x317fa7ee, 0x4a324f43, 0x3ffc39d9, 0x51b3fa8e, 0x7a4bee9f, 0x78bbc682,
+    0x9f5c0350, 0x02fe286c, 0x245ab686, 0xed6bf7d7, 0x0ac4988a, 0x3fe010fa, 0xc65fe369, 0xa45749cb,
+    0x2b84e537, 0xde9ff363, 0x20540f9a, 0xaa8c9b34, 0x5bc476b3, 0x1d574bd7, 0x929100ad, 0x4721de4d,
+    0x27df1b05, 0x58b18546, 0xb7e76764, 0xdf904e58, 0x97af57a1, 0xbd4dc433, 0xa6256dfd, 0xf63998f3,
+    0xf1e05833, 0xe20acf26, 0xf57fd9d6, 0x90300b4d, 0x89df4290, 0x68d01cbc, 0xcf893ee3, 0xcc42a046,
+    0x778e181b, 0x67265c76, 0xe981a4c4, 0x82991da1, 0x708f7294, 0xe6e2ae62, 0xfc441870, 0x95e1b0b6,
+    0x0445f825, 0x5a93b47f, 0x5e9cf4be, 0x84da71e7, 0x9d9582b0, 0x9bf835ef, 0x591f61e2, 0x43325985,
+    0x5d2de32e, 0x8d8fbf0f, 0x95b30f38, 0x07ad5b6e, 0x4e934edf, 0x3cd4990e, 0x9053e259, 0x5c41857d
+];
+
+/// go-ipfs-chunker's Rabin polynomial.
+const RABIN_POLYNOMIAL: u64 = 17437180132763653;
+/// Bytes in the fingerprint's sliding window.
+const WINDOW_SIZE: usize = 16;
+
+/// The rolling Rabin fingerprint of whyrusleeping/chunker, which go-ipfs
+/// uses for `rabin` chunking. Arithmetic is on polynomials over GF(2), held
+/// as bit patterns.
+struct Rabin {
+    out_table: [u64; 256],
+    mod_table: [u64; 256],
+    shift: u32,
+    mask: u64,
+    min: usize,
+    max: usize,
+    window: [u8; WINDOW_SIZE],
+    wpos: usize,
+    digest: u64,
+    /// Bytes in the current chunk so far.
+    count: usize
+}
+
+impl Rabin {
+    fn new(min: usize, avg: usize, max: usize) -> Rabin {
+        let degree = poly_degree(RABIN_POLYNOMIAL);
+        let mut out_table = [0; 256];
+        let mut mod_table = [0; 256];
+        for b in 0..256u64 {
+            // The fingerprint of `b` followed by a window's worth of zeros,
+            // which is what sliding `b` out of the window has to remove.
+            let mut h = poly_mod(b, RABIN_POLYNOMIAL);
+            for _ in 0..WINDOW_SIZE - 1 {
+                h = poly_mod(h << 8, RABIN_POLYNOMIAL);
+            }
+            out_table[b as usize] = h;
+            mod_table[b as usize] = poly_mod(b << degree, RABIN_POLYNOMIAL) | (b << degree);
+        }
+        let mut rabin = Rabin {
+            out_table,
+            mod_table,
+            shift: degree - 8,
+            mask: (1 << (63 - (avg as u64).leading_zeros())) - 1,
+            min,
+            max,
+            window: [0; WINDOW_SIZE],
+            wpos: 0,
+            digest: 0,
+            count: 0
+        };
+        rabin.reset();
+        rabin
+    }
+
+    /// Starts a new chunk. As in the Go code the window begins with a single
+    /// 1 byte slid in.
+    fn reset(&mut self) {
+        self.window = [0; WINDOW_SIZE];
+        self.wpos = 0;
+        self.digest = 0;
+        self.count = 0;
+        self.slide(1);
+    }
+
+    fn slide(&mut self, byte: u8) {
+        let out = self.window[self.wpos];
+        self.window[self.wpos] = byte;
+        self.digest ^= self.out_table[out as usize];
+        self.wpos = (self.wpos + 1) % WINDOW_SIZE;
+
+        let index = (self.digest >> self.shift) as usize;
+        self.digest = (self.digest << 8) | byte as u64;
+        self.digest ^= self.mod_table[index];
+    }
+
+    /// Adds `byte` to the current chunk and says whether the chunk ends
+    /// after it. The first `min - WINDOW_SIZE` bytes of a chunk are skipped
+    /// entirely, so that the window is full exactly when a cut first becomes
+    /// possible.
+    fn roll(&mut self, byte: u8) -> bool {
+        self.count += 1;
+        if self.count + WINDOW_SIZE <= self.min {
+            return false;
+        }
+        self.slide(byte);
+        self.count >= self.min && (self.digest & self.mask == 0 || self.count >= self.max)
+    }
+}
+
+fn poly_degree(p: u64) -> u32 {
+    63 - p.leading_zeros()
+}
+
+fn poly_mod(mut x: u64, p: u64) -> u64 {
+    while x != 0 && poly_degree(x) >= poly_degree(p) {
+        x ^= p << (poly_degree(x) - poly_degree(p));
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use reader::FileReader;
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::process::{Command, Stdio};
+
+    fn read_back(imported: &Imported) -> Vec<u8> {
+        let blocks: HashMap<Cid, Vec<u8>> = imported.blocks.iter().cloned().collect();
+        let mut content = Vec::new();
+        FileReader::new(&blocks, &imported.root).unwrap().read_to_end(&mut content).unwrap();
+        content
+    }
+
+    /// Deterministic bytes that do not repeat, so content-defined chunking
+    /// has something to find.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state: u32 = 2463534242;
+        (0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        }).collect()
+    }
+
+    /// The root `ipfs add --only-hash` gives `data` with the extra `args`.
+    fn ipfs_add(data: &[u8], args: &[&str]) -> Cid {
+        let mut child = Command::new("ipfs").args(["add", "--only-hash", "--quieter"]).args(args)
+                                            .stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()
+                                            .expect("ipfs is not on the PATH");
+        child.stdin.take().unwrap().write_all(data).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "ipfs add {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap().trim().parse().unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs the ipfs binary and an initialised repo"]
+    fn imports_match_ipfs_add() {
+        let cases: Vec<(ImportOptions, &[&str], usize)> = vec![
+            (ImportOptions::new(), &[], 3 << 20),
+            (ImportOptions::new().chunker(Chunker::Size(1024)), &["--chunker=size-1024"], 400 << 10),
+            (ImportOptions::new().chunker(Chunker::Size(1024)).layout(Layout::Trickle),
+             &["--chunker=size-1024", "--trickle"], 400 << 10),
+            (ImportOptions::new().chunker(Chunker::Size(1024)).cid_version(Version::V1),
+             &["--chunker=size-1024", "--cid-version=1"], 400 << 10),
+            (ImportOptions::new().chunker("rabin".parse().unwrap()), &["--chunker=rabin"], 3 << 20),
+            (ImportOptions::new().chunker("rabin-2048-8192-16384".parse().unwrap()),
+             &["--chunker=rabin-2048-8192-16384"], 2 << 20),
+            (ImportOptions::new().chunker(Chunker::Buzhash), &["--chunker=buzhash"], 3 << 20),
+            (ImportOptions::new().chunker(Chunker::Buzhash).layout(Layout::Trickle),
+             &["--chunker=buzhash", "--trickle"], 3 << 20),
+            (ImportOptions::new().cid_version(Version::V1).raw_leaves(true).mode(0o640)
+                                 .mtime(Mtime::new(1_700_000_000, 500)),
+             &["--cid-version=1", "--raw-leaves", "--mode=0640", "--mtime=1700000000", "--mtime-nsecs=500"], 5),
+            (ImportOptions::new().chunker(Chunker::Size(1024)).cid_version(Version::V1).raw_leaves(true).mode(0o640),
+             &["--chunker=size-1024", "--cid-version=1", "--raw-leaves", "--mode=0640"], 3000)
+        ];
+        for (options, args, len) in cases {
+            let data = noise(len);
+            assert_eq!(import_file(&data[..], &options).unwrap().root, ipfs_add(&data, args), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn small_files_match_go_ipfs() {
+        let imported = import_file(&b"This is a test\n"[..], &ImportOptions::new()).unwrap();
+        assert_eq!(imported.root.to_string(), "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt");
+        assert_eq!(imported.size, 23);
+
+        let empty = import_file(&b""[..], &ImportOptions::new()).unwrap();
+        assert_eq!(empty.root.to_string(), "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH");
+
+        let raw = import_file(&b"hello"[..], &ImportOptions::new().cid_version(Version::V1)).unwrap();
+        assert_eq!(raw.root.to_string(), "bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq");
+        assert_eq!(raw.blocks.len(), 1);
+    }
+
+    #[test]
+    fn balanced_trees_grow_a_level_when_full() {
+        let data = noise(MAX_LINKS + 1);
+        let imported = import_file(&data[..], &ImportOptions::new().chunker(Chunker::Size(1))).unwrap();
+        assert_eq!(read_back(&imported), data);
+
+        let (_, root) = imported.blocks.last().unwrap();
+        let root = ::dagpb::decode_dag_pb(root).unwrap();
+        assert_eq!(root.get_Links().len(), 2);
+    }
+
+    #[test]
+    fn metadata_is_recorded_in_the_root() {
+        let mtime = Mtime::new(1_700_000_000, 500);
+        let options = ImportOptions::new().cid_version(Version::V1).raw_leaves(true).mode(0o640).mtime(mtime);
+        let imported = import_file(&b"hello"[..], &options).unwrap();
+        assert_eq!(imported.root.codec(), Codec::DagPb);
+        assert_eq!(imported.blocks.len(), 1);
+        assert_eq!(read_back(&imported), b"hello");
+
+        let imported = import_file(&noise(3000)[..], &options.clone().chunker(Chunker::Size(1024))).unwrap();
+        assert!(imported.blocks.iter().all(|(cid, _)| cid.codec() == Codec::DagPb));
+
+        let blocks: HashMap<Cid, Vec<u8>> = imported.blocks.iter().cloned().collect();
+        let reader = FileReader::new(&blocks, &imported.root).unwrap();
+        assert_eq!(reader.attrs(), FileAttrs { mode: Some(0o640), mtime: Some(mtime) });
+    }
+
+    #[test]
+    fn trickle_layout_reads_back() {
+        let data = noise(MAX_LINKS * 3 + 7);
+        let options = ImportOptions::new().chunker(Chunker::Size(1)).layout(Layout::Trickle).raw_leaves(true);
+        let imported = import_file(&data[..], &options).unwrap();
+        assert_eq!(read_back(&imported), data);
+        assert_eq!(imported.root.version(), Version::V0);
+    }
+
+    #[test]
+    fn rabin_chunks_stay_within_bounds() {
+        let data = noise(20000);
+        let mut splitter = Splitter::new(&data[..], Chunker::Rabin { min: 64, avg: 256, max: 1024 });
+        let mut chunks = Vec::new();
+        while let Some(chunk) = splitter.next_chunk().unwrap() {
+            chunks.push(chunk);
+        }
+        assert!(chunks.len() > 1);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.len() >= 64 && chunk.len() <= 1024);
+        }
+        assert_eq!(chunks.concat(), data);
+    }
+
+    #[test]
+    fn chunker_specs_are_parsed() {
+        assert_eq!("size-1024".parse::<Chunker>().unwrap(), Chunker::Size(1024));
+        assert_eq!("rabin".parse::<Chunker>().unwrap(), Chunker::Rabin { min: 87381, avg: 262144, max: 393216 });
+        assert_eq!("rabin-16-32-64".parse::<Chunker>().unwrap().to_string(), "rabin-16-32-64");
+        assert!("size-0".parse::<Chunker>().is_err());
+        assert!("rabin-8-32-64".parse::<Chunker>().is_err());
+        assert_eq!("buzhash".parse::<Chunker>().unwrap(), Chunker::Buzhash);
+        assert_eq!(Chunker::Buzhash.to_string(), "buzhash");
+        assert!("buzhash-1024".parse::<Chunker>().is_err());
+    }
+
+    #[test]
+    fn buzhash_table_is_balanced() {
+        for bit in 0..32 {
+            let set = BUZHASH_TABLE.iter().filter(|&&h| h & (1 << bit) != 0).count();
+            assert_eq!(set, 128, "bit {}", bit);
+        }
+    }
+
+    #[test]
+    fn buzhash_chunks_stay_within_bounds() {
+        let data = noise(3 * BUZ_MAX);
+        let mut splitter = Splitter::new(&data[..], Chunker::Buzhash);
+        let mut chunks = Vec::new();
+        while let Some(chunk) = splitter.next_chunk().unwrap() {
+            chunks.push(chunk);
+        }
+        assert!(chunks.len() > 3);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.len() >= BUZ_MIN && chunk.len() <= BUZ_MAX);
+        }
+        assert_eq!(chunks.concat(), data);
+
+        // A window of one repeated byte hashes to 0 or to all ones, as its
+        // table entry has an even or odd number of bits set. Runs of zeros
+        // are cut as early as possible, and runs of ones never.
+        for &(byte, len) in &[(0u8, BUZ_MIN), (1u8, BUZ_MAX)] {
+            let run = vec![byte; 2 * len + 10];
+            let mut splitter = Splitter::new(&run[..], Chunker::Buzhash);
+            assert_eq!(splitter.next_chunk().unwrap().unwrap().len(), len);
+            assert_eq!(splitter.next_chunk().unwrap().unwrap().len(), len);
+            assert_eq!(splitter.next_chunk().unwrap().unwrap().len(), 10);
+            assert!(splitter.next_chunk().unwrap().is_none());
+        }
+    }
+}
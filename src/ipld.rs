@@ -0,0 +1,152 @@
+//! The IPLD data model, and its DAG-JSON encoding, which is the form the
+//! daemon's `dag` commands exchange data in. Links are written as
+//! `{"/": "<cid>"}` and bytes as `{"/": {"bytes": "<base64>"}}`.
+
+use cid::Cid;
+use error::IPFSError;
+use multibase::Base;
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+/// A value in the IPLD data model, whatever codec it was stored with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ipld {
+    Null,
+    Bool(bool),
+    /// Wide enough for both the signed and unsigned 64-bit integers that
+    /// dag-cbor can hold.
+    Integer(i128),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<Ipld>),
+    Map(BTreeMap<String, Ipld>),
+    Link(Cid)
+}
+
+impl Ipld {
+    /// Looks up `key` if this is a map.
+    pub fn get(&self, key: &str) -> Option<&Ipld> {
+        match *self {
+            Ipld::Map(ref map) => map.get(key),
+            _ => None
+        }
+    }
+
+    /// Decodes a parsed DAG-JSON document.
+    pub fn from_dag_json(json: &Value) -> Result<Ipld, IPFSError> {
+        Ok(match *json {
+            Value::Null => Ipld::Null,
+            Value::Bool(b) => Ipld::Bool(b),
+            Value::Number(ref n) => match (n.as_i64(), n.as_u64(), n.as_f64()) {
+                (Some(n), _, _) => Ipld::Integer(n as i128),
+                (_, Some(n), _) => Ipld::Integer(n as i128),
+                (_, _, Some(n)) => Ipld::Float(n),
+                _ => return Err(IPFSError::InvalidBlock(format!("unrepresentable number {}", n)))
+            },
+            Value::String(ref s) => Ipld::String(s.clone()),
+            Value::Array(ref items) => Ipld::List(items.iter().map(Ipld::from_dag_json).collect::<Result<_, _>>()?),
+            Value::Object(ref map) => {
+                if let Some(reserved) = map.get("/") {
+                    if map.len() == 1 {
+                        return reserved_value(reserved);
+                    }
+                }
+                let mut out = BTreeMap::new();
+                for (key, value) in map {
+                    out.insert(key.clone(), Ipld::from_dag_json(value)?);
+                }
+                Ipld::Map(out)
+            }
+        })
+    }
+
+    /// Encodes this value as DAG-JSON. Map keys come out sorted, as the
+    /// canonical form requires. DAG-JSON has no NaN or infinities; they are
+    /// written as `null`. Integers that fit in neither an `i64` nor a `u64`
+    /// are an error rather than being truncated.
+    pub fn to_dag_json(&self) -> Result<Value, IPFSError> {
+        Ok(match *self {
+            Ipld::Null => Value::Null,
+            Ipld::Bool(b) => Value::Bool(b),
+            Ipld::Integer(n) => match (i64::try_from(n), u64::try_from(n)) {
+                (Ok(n), _) => Value::from(n),
+                (_, Ok(n)) => Value::from(n),
+                _ => return Err(IPFSError::InvalidBlock(format!("integer {} does not fit in 64 bits", n)))
+            },
+            Ipld::Float(n) => Number::from_f64(n).map_or(Value::Null, Value::Number),
+            Ipld::String(ref s) => Value::String(s.clone()),
+            Ipld::Bytes(ref bytes) => {
+                let mut inner = Map::new();
+                inner.insert("bytes".to_string(), Value::String(Base::Base64.encode(bytes)));
+                reserved_json(Value::Object(inner))
+            }
+            Ipld::List(ref items) => Value::Array(items.iter().map(Ipld::to_dag_json).collect::<Result<_, _>>()?),
+            Ipld::Map(ref map) => {
+                let mut out = Map::new();
+                for (key, value) in map {
+                    out.insert(key.clone(), value.to_dag_json()?);
+                }
+                Value::Object(out)
+            }
+            Ipld::Link(ref cid) => reserved_json(Value::String(cid.to_string()))
+        })
+    }
+}
+
+/// Decodes the value under a lone `"/"` key: a link if it is a string, bytes
+/// if it is `{"bytes": ...}`.
+fn reserved_value(reserved: &Value) -> Result<Ipld, IPFSError> {
+    if let Some(cid) = reserved.as_str() {
+        return Ok(Ipld::Link(cid.parse()?));
+    }
+    match reserved.get("bytes").and_then(|b| b.as_str()) {
+        Some(bytes) if reserved.as_object().map(|o| o.len()) == Some(1) => {
+            Ok(Ipld::Bytes(Base::Base64.decode(bytes.trim_end_matches('='))?))
+        }
+        _ => Err(IPFSError::InvalidBlock(format!("malformed DAG-JSON reserved object {}", reserved)))
+    }
+}
+
+fn reserved_json(value: Value) -> Value {
+    let mut map = Map::new();
+    map.insert("/".to_string(), value);
+    Value::Object(map)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn links_and_bytes_round_trip() {
+        let text = r#"{"data":{"/":{"bytes":"aGVsbG8"}},"n":-3,"next":{"/":"bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq"},"tags":["a",null,1.5]}"#;
+        let value = Ipld::from_dag_json(&serde_json::from_str::<Value>(text).unwrap()).unwrap();
+        assert_eq!(value.get("data"), Some(&Ipld::Bytes(b"hello".to_vec())));
+        assert_eq!(value.get("n"), Some(&Ipld::Integer(-3)));
+        match value.get("next") {
+            Some(Ipld::Link(cid)) => assert_eq!(cid.to_string(), "bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq"),
+            other => panic!("expected a link, got {:?}", other)
+        }
+        assert_eq!(value.to_dag_json().unwrap().to_string(), text);
+    }
+
+    #[test]
+    fn integers_beyond_64_bits_are_rejected() {
+        assert_eq!(Ipld::Integer(u64::MAX as i128).to_dag_json().unwrap().to_string(), u64::MAX.to_string());
+        assert_eq!(Ipld::Integer(i64::MIN as i128).to_dag_json().unwrap().to_string(), i64::MIN.to_string());
+        assert!(Ipld::Integer(u64::MAX as i128 + 1).to_dag_json().is_err());
+        assert!(Ipld::List(vec![Ipld::Integer(i64::MIN as i128 - 1)]).to_dag_json().is_err());
+    }
+
+    #[test]
+    fn malformed_reserved_objects_are_rejected() {
+        let json = serde_json::from_str::<Value>(r#"{"/":{"bytes":"aGVsbG8","extra":1}}"#).unwrap();
+        assert!(Ipld::from_dag_json(&json).is_err());
+        let json = serde_json::from_str::<Value>(r#"{"/":"not a cid"}"#).unwrap();
+        assert!(Ipld::from_dag_json(&json).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use multipart::Multipart;
+#[cfg(feature = "blocking")]
+use response::read_json;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::string_field;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+#[cfg(feature = "blocking")]
+use std::io::Read;
+#[cfg(feature = "blocking")]
+use IPFS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    Ed25519,
+    /// RSA with the given modulus size in bits.
+    Rsa(u32)
+}
+
+/// Serialization used by `key_import` and `key_export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// The libp2p `PrivateKey` protobuf, unencrypted.
+    Libp2pProtobuf,
+    /// A PKCS #8 PEM file, unencrypted.
+    PemPkcs8
+}
+
+impl KeyFormat {
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn as_str(&self) -> &'static str {
+        match *self {
+            KeyFormat::Libp2pProtobuf => "libp2p-protobuf-cleartext",
+            KeyFormat::PemPkcs8 => "pem-pkcs8-cleartext"
+        }
+    }
+}
+
+/// A key in the node's keystore. `id` is the peer ID derived from it, which
+/// is also the IPNS name records published with it live under.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key {
+    pub name: String,
+    pub id: String
+}
+
+/// The result of `key_rename`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRename {
+    pub was: String,
+    pub now: String,
+    pub id: String,
+    /// Whether an existing key called `now` was replaced.
+    pub overwrite: bool
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    pub fn key_gen(&self, name: &str, kind: KeyType) -> Result<Key, IPFSError> {
+        parse_key(&read_json(self.request("key/gen", &gen_args(name, kind))?)?)
+    }
+
+    pub fn key_list(&self) -> Result<Vec<Key>, IPFSError> {
+        keys_field(&read_json(self.request("key/list", &[("l", "true".to_string())])?)?)
+    }
+
+    /// Renames key `old` to `new`, replacing any existing `new` if `force`.
+    pub fn key_rename(&self, old: &str, new: &str, force: bool) -> Result<KeyRename, IPFSError> {
+        let args = [("arg", old.to_string()), ("arg", new.to_string()), ("force", force.to_string())];
+        parse_rename(&read_json(self.request("key/rename", &args)?)?)
+    }
+
+    /// Removes the named key and returns it.
+    pub fn key_rm(&self, name: &str) -> Result<Key, IPFSError> {
+        removed_key(&read_json(self.request("key/rm", &[("arg", name.to_string())])?)?)
+    }
+
+    /// Imports a private key read from `key` under `name`.
+    pub fn key_import<R: Read>(&self, name: &str, key: R, format: KeyFormat) -> Result<Key, IPFSError> {
+        let mut form = Multipart::new();
+        form.add_reader("key", key);
+        let args = [("arg", name.to_string()), ("format", format.as_str().to_string())];
+        parse_key(&read_json(self.request_with_body("key/import", &args, &mut form)?)?)
+    }
+
+    /// Exports the named private key. Some daemon versions refuse to do this
+    /// over the HTTP API, in which case their error is returned.
+    pub fn key_export(&self, name: &str, format: KeyFormat) -> Result<Vec<u8>, IPFSError> {
+        let args = [("arg", name.to_string()), ("format", format.as_str().to_string())];
+        let mut res = self.request("key/export", &args)?;
+        let mut key = Vec::new();
+        res.read_to_end(&mut key)?;
+        Ok(key)
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn gen_args(name: &str, kind: KeyType) -> Vec<(&'static str, String)> {
+    let mut args = vec![("arg", name.to_string())];
+    match kind {
+        KeyType::Ed25519 => args.push(("type", "ed25519".to_string())),
+        KeyType::Rsa(bits) => {
+            args.push(("type", "rsa".to_string()));
+            args.push(("size", bits.to_string()));
+        }
+    }
+    args
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_key(json: &Value) -> Result<Key, IPFSError> {
+    Ok(Key { name: string_field(json, "Name")?, id: string_field(json, "Id")? })
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_rename(json: &Value) -> Result<KeyRename, IPFSError> {
+    Ok(KeyRename {
+        was: string_field(json, "Was")?,
+        now: string_field(json, "Now")?,
+        id: string_field(json, "Id")?,
+        overwrite: json.get("Overwrite").and_then(|o| o.as_bool()).unwrap_or(false)
+    })
+}
+
+/// `key/rm` answers with a list holding the one key it removed.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn removed_key(json: &Value) -> Result<Key, IPFSError> {
+    match keys_field(json)?.pop() {
+        Some(key) => Ok(key),
+        None => Err(IPFSError::UnexpectedResponse("key/rm did not return the key".to_string()))
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn keys_field(json: &Value) -> Result<Vec<Key>, IPFSError> {
+    let keys = match json.get("Keys").and_then(|keys| keys.as_array()) {
+        Some(keys) => keys,
+        None => return Err(IPFSError::UnexpectedResponse("missing array field \"Keys\"".to_string()))
+    };
+    keys.iter().map(parse_key).collect()
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+
+    use super::*;
+    use serde_json::{self, Value};
+
+    #[test]
+    fn key_list_is_parsed() {
+        let json = serde_json::from_str::<Value>(r#"{"Keys":[{"Name":"self","Id":"12D3KooWQ8M5Gb8KLmEfcJ2j5PJ7QrBT3YMtV5Yb8d1pTK5mUsjU"},
+                                             {"Name":"releases","Id":"k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8"}]}"#)
+                       .unwrap();
+        let keys = keys_field(&json).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[1], Key { name: "releases".to_string(),
+                                  id: "k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8".to_string() });
+    }
+}
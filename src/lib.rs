@@ -1,109 +1,402 @@
-extern crate hyper;
+//! A client for the IPFS HTTP API.
+//!
+//! The blocking `IPFS` client is built with the default `blocking` feature.
+//! The `async` feature adds `AsyncIPFS`, whose methods return futures and
+//! streams for use inside a tokio runtime, and the `tls` feature lets either
+//! reach the daemon over HTTPS. Timeouts and retries are set with
+//! `ClientOptions`, and streams can be stopped early with a `CancelHandle`.
+//! The offline parts of the crate (CIDs, codecs, the importer and DAG
+//! readers) are available whatever the features.
+
+extern crate blake2;
+#[cfg(feature = "async")]
+extern crate bytes;
+#[cfg(all(test, feature = "async"))]
+extern crate futures_executor;
+#[cfg(feature = "async")]
+extern crate futures_util;
+extern crate http;
 extern crate protobuf;
-extern crate rust_base58;
+#[cfg(any(feature = "blocking", feature = "async"))]
+extern crate reqwest;
+extern crate serde_json;
+extern crate sha2;
+extern crate sha3;
+#[cfg(feature = "blocking")]
+extern crate tar;
+#[cfg(feature = "async")]
+extern crate tokio;
+extern crate url;
 
-mod merkledag;
+mod add;
+#[cfg(feature = "async")]
+mod async_client;
+mod attrs;
+mod blocks;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod cancel;
+mod cid;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod client;
+mod dag;
+mod dagpb;
+mod endpoint;
+mod error;
+mod get;
+mod hamt;
+mod importer;
+mod ipld;
+mod key;
+mod ls;
+// merkledag.rs and unixfs.rs are generated by rust-protobuf 2.28 from the
+// `.proto` files beside them, with lint attributes newer compilers object to.
+#[allow(renamed_and_removed_lints, unknown_lints, mismatched_lifetime_syntaxes)]
+pub mod merkledag;
+mod mfs;
+mod multibase;
+mod multihash;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod multipart;
+mod name;
+mod object;
+mod pin;
+mod reader;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod response;
+#[allow(renamed_and_removed_lints, unknown_lints, mismatched_lifetime_syntaxes)]
 mod unixfs;
+mod varint;
 
-use hyper::Client;
-use hyper::header::Connection;
-use protobuf::core::Message;
-use rust_base58::ToBase58;
-use std::str::from_utf8;
+pub use add::{AddOptions, AddedObject};
+#[cfg(feature = "async")]
+pub use async_client::{cancellable, AsyncIPFS, AsyncMfs, IpfsFuture, IpfsStream};
+pub use attrs::{FileAttrs, Mtime};
+pub use blocks::{BlockPutOptions, BlockSource, BlockStat};
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use cancel::CancelHandle;
+pub use cid::{Cid, Codec, Version};
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use client::{ClientOptions, RetryPolicy};
+pub use dag::{DagPutOptions, DagResolved, DagStat};
+pub use dagpb::{decode_dag_pb, encode_dag_pb, DagPbBuilder};
+pub use endpoint::Endpoint;
+pub use error::{ApiError, IPFSError};
+pub use get::{Archive, GetOptions};
+#[cfg(feature = "blocking")]
+pub use get::GetProgress;
+pub use hamt::{DirEntry, ShardWriter, ShardedDirectory};
+pub use importer::{import_file, import_path, Chunker, ImportOptions, Imported, Layout};
+pub use ipld::Ipld;
+pub use key::{Key, KeyFormat, KeyRename, KeyType};
+pub use ls::{Entry, LsOptions};
+#[cfg(feature = "blocking")]
+pub use ls::LsStream;
+pub use mfs::{FileStat, FileType, MfsEntry, MkdirOptions, WriteOptions};
+#[cfg(feature = "blocking")]
+pub use mfs::Mfs;
+pub use multibase::Base;
+pub use multihash::{HashFunction, Multihash};
+pub use name::{NameEntry, PublishOptions, ResolveOptions};
+pub use object::{ChangeType, ObjectChange, ObjectLink, ObjectStat, ObjectTemplate};
+pub use pin::{BadNode, Pin, PinType, PinVerification};
+pub use reader::FileReader;
+#[cfg(feature = "blocking")]
+pub use response::{JsonStream, ResponseReader};
 
-#[derive(Debug)]
-pub enum IPFSError {
-    ConnectionError,
-    NoSuchHash
-}
+#[cfg(feature = "blocking")]
+use multipart::Multipart;
+#[cfg(feature = "blocking")]
+use reqwest::blocking::{Body, Client, Response};
+#[cfg(feature = "blocking")]
+use reqwest::header::CONTENT_TYPE;
+#[cfg(feature = "blocking")]
+use std::io::Read;
+#[cfg(feature = "blocking")]
+use std::panic;
+#[cfg(feature = "blocking")]
+use std::sync::mpsc;
+#[cfg(feature = "blocking")]
+use std::thread;
+#[cfg(feature = "blocking")]
+use std::time::Duration;
 
+/// The blocking client. Connections to the daemon are pooled and kept alive
+/// between calls, and shared by clones. Like any `reqwest::blocking` client,
+/// it must not be created or used from within an async runtime.
+#[cfg(feature = "blocking")]
+#[derive(Clone)]
 pub struct IPFS {
-    host: String,
-    port: u16,
-    apistring: String
+    endpoint: Endpoint,
+    client: Client,
+    retry: RetryPolicy,
+    timeout: Option<Duration>
 }
 
+#[cfg(feature = "blocking")]
 impl IPFS {
     pub fn new (host: String, port: u16) -> IPFS {
-        IPFS { host: host, port: port, apistring: "/api/v0/".to_string() }
+        IPFS::with_endpoint(Endpoint::new(&host, port))
+    }
+
+    /// A client for the API at `endpoint`, e.g.
+    /// `"/dns4/node.example/tcp/443/https".parse()?`.
+    pub fn with_endpoint(endpoint: Endpoint) -> IPFS {
+        IPFS::with_options(endpoint, &ClientOptions::new()).expect("the default client options are always valid")
+    }
+
+    /// A client for the API at `endpoint` with the given headers,
+    /// credentials, TLS settings, timeouts and retry policy.
+    pub fn with_options(endpoint: Endpoint, options: &ClientOptions) -> Result<IPFS, IPFSError> {
+        Ok(IPFS {
+            endpoint,
+            client: options.blocking_client()?,
+            retry: options.retry_policy().clone(),
+            timeout: None
+        })
+    }
+
+    pub fn from_multiaddr(addr: &str) -> Result<IPFS, IPFSError> {
+        Ok(IPFS::with_endpoint(Endpoint::from_multiaddr(addr)?))
+    }
+
+    pub fn from_url(url: &str) -> Result<IPFS, IPFSError> {
+        Ok(IPFS::with_endpoint(Endpoint::from_url(url)?))
+    }
+
+    /// A client for the local daemon, found through the `api` file of
+    /// `$IPFS_PATH` or `~/.ipfs`.
+    pub fn discover() -> Result<IPFS, IPFSError> {
+        Ok(IPFS::with_endpoint(Endpoint::discover()?))
+    }
+
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
     }
 
+    /// A client sharing this one's connections whose requests must each be
+    /// over, response and all, within `timeout`, e.g.
+    /// `ipfs.with_timeout(Duration::from_secs(5)).block_stat(&cid)`.
+    pub fn with_timeout(&self, timeout: Duration) -> IPFS {
+        IPFS { timeout: Some(timeout), ..self.clone() }
+    }
+
+    /// POSTs `cmd` with the given query arguments, since the daemon refuses
+    /// GET requests, and returns the response if the daemon reported
+    /// success. Error statuses are turned into an `IPFSError` carrying the
+    /// daemon's error body. Failures are retried as the client's
+    /// `RetryPolicy` allows.
+    fn request(&self, cmd: &str, args: &[(&str, String)]) -> Result<Response, IPFSError> {
+        let url = self.endpoint.url(cmd, args);
+        let mut attempt = 0;
+        loop {
+            let mut request = self.client.post(url.as_str());
+            if let Some(timeout) = self.timeout {
+                request = request.timeout(timeout);
+            }
+            let err = match request.send().map_err(IPFSError::from).and_then(check_status) {
+                Ok(res) => return Ok(res),
+                Err(err) => err
+            };
+            match self.retry.retry_after(cmd, attempt, &err) {
+                Some(backoff) => thread::sleep(backoff),
+                None => return Err(err)
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Like `request`, but POSTs `form` as a streamed multipart body. The
+    /// form may borrow from the caller, so it is read on this thread and
+    /// handed a chunk at a time to the one sending the request.
+    fn request_with_body(&self, cmd: &str, args: &[(&str, String)], form: &mut Multipart)
+                         -> Result<Response, IPFSError> {
+        let (sender, receiver) = mpsc::sync_channel(4);
+        let mut request = self.client.post(self.endpoint.url(cmd, args).as_str())
+                                     .header(CONTENT_TYPE, form.content_type())
+                                     .body(Body::new(multipart::Chunks::new(receiver)));
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        let sending = thread::spawn(move || request.send());
+        let written = form.write_chunks(&sender);
+        drop(sender);
+        let res = sending.join().unwrap_or_else(|panic| panic::resume_unwind(panic));
+        written?;
+        check_status(res?)
+    }
+
+    /// Fetches a dag-pb node with `object/get`. When the path names a block
+    /// directly, its bytes are checked against the CID before decoding.
     fn call(&self, cmd: &str, args: Vec<String>) -> Result<merkledag::PBNode, IPFSError> {
-        let connection = Client::new();
-        //let res = connection.get(&"http://" + self.host + ":" + self.port + "cat?arg=" + path);
-        let mut res = connection.get(&format!("http://{}:{}{}{}?arg={}&encoding=protobuf", 
-                                         self.host, self.port, self.apistring, cmd, args[0]))
-                                         .header(Connection::close())
-                                         .send().unwrap();
-        let mut object = merkledag::PBNode::new();
-        object.merge_from(&mut protobuf::CodedInputStream::new(&mut res));
-
-        return Ok(object); // This is a hack, we should not be unwrapping above and instead doing
-                         // something with errors.
+        let mut res = self.request(cmd, &[("arg", args[0].clone()), ("encoding", "protobuf".to_string())])?;
+        let mut block = Vec::new();
+        res.read_to_end(&mut block)?;
+        object::decode_object(&args[0], &block)
     }
     
     pub fn cat (&self, path: String) -> Result<String, IPFSError> {
-        let result = self.call("/object/get", vec![path]);
-        println!("{:?}", result);
-        match result {
-            Ok(node) => {
-                let mut content = unixfs::Data::new();
-                content.merge_from_bytes(&mut node.get_Data());
-                return Ok(from_utf8(content.get_Data()).unwrap().to_string());
-            }
-            Err(error) => Err(error)
-        }
+        let mut content = Vec::new();
+        self.cat_reader(&path, None, None)?.read_to_end(&mut content)?;
+        String::from_utf8(content).map_err(|err| IPFSError::Utf8(err.utf8_error()))
     }
 
-    pub fn ls (&self, path: String) -> Result<Vec<(String, u64, String)>, IPFSError> {
-        let result = self.call("/object/get", vec![path]);
-        match result {
-            Ok(node) => {
-                let links = node.get_Links();
-                let mut link_vec = Vec::new();
-                for link in links {
-                    link_vec.push((link.get_Hash().to_base58(), link.get_Tsize(), link.get_Name().to_string()));
-                }
-                return Ok(link_vec);
-            }
-           Err(error) => Err(error)
+    /// Streams the full contents of the file at `path`, optionally starting
+    /// at byte `offset` and stopping after `length` bytes. `/ipns/` paths are
+    /// resolved first.
+    pub fn cat_reader(&self, path: &str, offset: Option<u64>, length: Option<u64>)
+                      -> Result<ResponseReader, IPFSError> {
+        let mut args = vec![("arg", self.resolve_path(path)?)];
+        if let Some(offset) = offset {
+            args.push(("offset", offset.to_string()));
         }
+        if let Some(length) = length {
+            args.push(("length", length.to_string()));
+        }
+        Ok(ResponseReader::new(self.request("cat", &args)?))
     }
 }
 
+#[cfg(feature = "blocking")]
+fn check_status(mut res: Response) -> Result<Response, IPFSError> {
+    if res.status().is_success() {
+        return Ok(res);
+    }
+
+    let mut body = String::new();
+    res.read_to_string(&mut body)?;
+    Err(IPFSError::from_response(res.status(), body))
+}
+
         
-#[cfg(test)]
+#[cfg(all(test, feature = "blocking"))]
 mod tests {
 
     use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+
+    /// The request lines and bodies a `mock_daemon` was sent, in order.
+    pub type Requests = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+    /// A client for a stand-in daemon on a local port, which answers every
+    /// request with a 200 and whatever `respond` makes of its request line.
+    pub fn mock_daemon<F>(respond: F) -> (IPFS, Requests)
+        where F: Fn(&str) -> Vec<u8> + Send + Sync + 'static
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let respond = Arc::new(respond);
+        let requests = Requests::default();
+        let log = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (respond, log) = (respond.clone(), log.clone());
+                thread::spawn(move || serve(stream.unwrap(), &*respond, &log));
+            }
+        });
+        (IPFS::new("127.0.0.1".to_string(), port), requests)
+    }
+
+    fn serve(stream: TcpStream, respond: &dyn Fn(&str) -> Vec<u8>, log: &Requests) {
+        let mut reader = BufReader::new(stream);
+        loop {
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                return;
+            }
+            let (mut length, mut chunked) = (0, false);
+            let mut header = String::new();
+            while header != "\r\n" {
+                header.clear();
+                reader.read_line(&mut header).unwrap();
+                let lower = header.to_ascii_lowercase();
+                if let Some(value) = lower.strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                chunked |= lower.starts_with("transfer-encoding:") && lower.contains("chunked");
+            }
+            let mut body = Vec::new();
+            if chunked {
+                loop {
+                    let mut size = String::new();
+                    reader.read_line(&mut size).unwrap();
+                    let size = usize::from_str_radix(size.trim(), 16).unwrap();
+                    let mut chunk = vec![0; size + 2];
+                    reader.read_exact(&mut chunk).unwrap();
+                    body.extend_from_slice(&chunk[..size]);
+                    if size == 0 {
+                        break;
+                    }
+                }
+            } else {
+                body.resize(length, 0);
+                reader.read_exact(&mut body).unwrap();
+            }
+            let response = respond(&request_line);
+            log.lock().unwrap().push((request_line, body));
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", response.len());
+            reader.get_mut().write_all(head.as_bytes()).unwrap();
+            reader.get_mut().write_all(&response).unwrap();
+        }
+    }
 
     #[test]
+    fn commands_are_posted() {
+        let (server, requests) = mock_daemon(|_| b"This is a test".to_vec());
+        assert_eq!(server.cat("/ipfs/QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".to_string()).unwrap(), "This is a test");
+        assert!(requests.lock().unwrap()[0].0.starts_with("POST /api/v0/cat?"));
+    }
+
+    #[test]
+    #[ignore = "needs a daemon on localhost:5001"]
     fn cat_returns_correct_value() {
         let server = IPFS::new("localhost".to_string(), 5001);
-        assert_eq!("This is a test", server.cat("/ipfs/QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".to_string()).unwrap().trim_right_matches('\n'));
+        assert_eq!("This is a test", server.cat("/ipfs/QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".to_string()).unwrap().trim_end_matches('\n'));
+    }
+
+    #[test]
+    #[ignore = "needs a daemon on localhost:5001"]
+    fn cat_reader_honours_offset_and_length() {
+        let server = IPFS::new("localhost".to_string(), 5001);
+        let mut content = String::new();
+        server.cat_reader("/ipfs/QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt", Some(5), Some(2)).unwrap()
+              .read_to_string(&mut content).unwrap();
+        assert_eq!(content, "is");
+    }
+
+    #[test]
+    #[ignore = "needs a daemon on localhost:5001"]
+    fn add_bytes_returns_hash() {
+        let server = IPFS::new("localhost".to_string(), 5001);
+        let added = server.add_bytes("test.txt", b"This is a test\n", &AddOptions::new().only_hash(true)).unwrap();
+        assert_eq!(added, vec![AddedObject { name: "test.txt".to_string(),
+                                             hash: "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".parse().unwrap(),
+                                             size: 23 }]);
     }
 
     #[test]
+    #[ignore = "needs a daemon on localhost:5001"]
     fn ls_returns_vec_of_correct_values() {
         let server = IPFS::new("localhost".to_string(), 5001);
         // Use the ipfs.io website within IPFS as test
-        let response = server.ls("/ipfs/QmeYYwD4y4DgVVdAzhT7wW5vrvmbKPQj8wcV2pAzjbj886".to_string());
-        let expect = vec![("QmTkQCrspeQDEiFBvphH3ULHYNWr3aXpymGiyjMrrUimtZ".to_string(), 1717422, "blog".to_string()),
-                          ("Qma4JRMJgwjBhsaBkEGXh682zphSoNi67k7pgGxkqTouiK".to_string(), 131161, "docs".to_string()),
-                          ("QmSXujSW6xykhU5wECQRrSW83YRjz8M8t93mfKddKm9ncL".to_string(), 11894, "index.html".to_string()),
-                          ("QmNXFHU3KzhzXN9pZ5Bdghk3LYGj58Xzi7WvMVVoykdLxA".to_string(), 136279, "media".to_string()),
-                          ("QmSEBfiu7BmQkoHuBPAjs9tHqvM61NiKfFfRJR4UnTrorx".to_string(), 6223432, "styles".to_string())];
+        let response = server.ls("/ipfs/QmeYYwD4y4DgVVdAzhT7wW5vrvmbKPQj8wcV2pAzjbj886", &LsOptions::new().cumulative_sizes(true));
+        let expect = vec![("QmTkQCrspeQDEiFBvphH3ULHYNWr3aXpymGiyjMrrUimtZ".parse::<Cid>().unwrap(), 1717422, "blog".to_string()),
+                          ("Qma4JRMJgwjBhsaBkEGXh682zphSoNi67k7pgGxkqTouiK".parse::<Cid>().unwrap(), 131161, "docs".to_string()),
+                          ("QmSXujSW6xykhU5wECQRrSW83YRjz8M8t93mfKddKm9ncL".parse::<Cid>().unwrap(), 11894, "index.html".to_string()),
+                          ("QmNXFHU3KzhzXN9pZ5Bdghk3LYGj58Xzi7WvMVVoykdLxA".parse::<Cid>().unwrap(), 136279, "media".to_string()),
+                          ("QmSEBfiu7BmQkoHuBPAjs9tHqvM61NiKfFfRJR4UnTrorx".parse::<Cid>().unwrap(), 6223432, "styles".to_string())];
         let checked = match response {
             Ok(data) => data,
-            Err(error) => panic!(error)
+            Err(error) => std::panic::panic_any(error)
         };
         
-        if checked.len() == 0 {
+        if checked.is_empty() {
             panic!("No results returned.");
         }
 
-        for (asked, gotten) in checked.iter().zip(&expect) {
-            assert_eq!(asked, gotten);
+        for (entry, gotten) in checked.iter().zip(&expect) {
+            assert_eq!((entry.cid.clone(), entry.cumulative_size.unwrap(), entry.name.clone()), *gotten);
         }
     }
 }
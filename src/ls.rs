@@ -0,0 +1,300 @@
+//! Directory listings through `/api/v0/ls`, which reads through HAMT
+//! sharding and can look up the type and size of every entry.
+
+use attrs::Mtime;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use attrs;
+#[cfg(feature = "blocking")]
+use blocks::BlockSource;
+#[cfg(feature = "blocking")]
+use cancel::CancelHandle;
+use cid::Cid;
+#[cfg(feature = "blocking")]
+use cid::Codec;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use hamt::ShardedDirectory;
+#[cfg(feature = "blocking")]
+use merkledag::PBNode;
+use mfs::FileType;
+#[cfg(feature = "blocking")]
+use protobuf::Message;
+#[cfg(feature = "blocking")]
+use response::ResponseLines;
+#[cfg(feature = "blocking")]
+use response::read_json;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::{string_field, u64_field};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+#[cfg(feature = "blocking")]
+use std::collections::{HashMap, VecDeque};
+#[cfg(feature = "blocking")]
+use unixfs::{Data, Data_DataType};
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// Options for `ls` and `ls_stream`. The type and size lookups are on by
+/// default; turning them off saves the daemon fetching the root block of
+/// every entry. Cumulative sizes are off by default.
+#[derive(Debug, Clone, Default)]
+pub struct LsOptions {
+    resolve_type: Option<bool>,
+    size: Option<bool>,
+    cumulative_sizes: bool
+}
+
+impl LsOptions {
+    pub fn new() -> LsOptions {
+        LsOptions::default()
+    }
+
+    /// Look up whether each entry is a file, directory or symlink.
+    pub fn resolve_type(mut self, resolve: bool) -> LsOptions {
+        self.resolve_type = Some(resolve);
+        self
+    }
+
+    /// Look up the content size of each file.
+    pub fn size(mut self, size: bool) -> LsOptions {
+        self.size = Some(size);
+        self
+    }
+
+    /// Fill in each entry's `cumulative_size` in `ls`. The daemon does not
+    /// report it, so this fetches the directory's block, and every shard of
+    /// a sharded directory, to read the sizes from its links.
+    pub fn cumulative_sizes(mut self, cumulative_sizes: bool) -> LsOptions {
+        self.cumulative_sizes = cumulative_sizes;
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = Vec::new();
+        if let Some(resolve) = self.resolve_type {
+            args.push(("resolve-type", resolve.to_string()));
+        }
+        if let Some(size) = self.size {
+            args.push(("size", size.to_string()));
+        }
+        args
+    }
+}
+
+/// One entry of a directory listing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub cid: Cid,
+    /// Size of the whole DAG below the entry, as recorded in the directory's
+    /// link to it. Only set by `ls`, and only when
+    /// `LsOptions::cumulative_sizes` asks for it.
+    pub cumulative_size: Option<u64>,
+    /// Unset unless the type was resolved.
+    pub kind: Option<FileType>,
+    /// Size of a file's content; zero for directories, or if sizes were not
+    /// looked up.
+    pub size: u64,
+    pub mode: Option<u32>,
+    pub mtime: Option<Mtime>,
+    /// Where a symlink points.
+    pub target: Option<String>
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Lists the directory at `path`. Listing a chunked file gives its
+    /// blocks, with empty names.
+    pub fn ls(&self, path: &str, options: &LsOptions) -> Result<Vec<Entry>, IPFSError> {
+        let path = self.resolve_path(path)?;
+        let mut args = vec![("arg", path.clone())];
+        args.extend(options.to_args());
+        let json = read_json(self.request("ls", &args)?)?;
+        let object = match json.get("Objects").and_then(|objects| objects.as_array()).and_then(|o| o.first()) {
+            Some(object) => object,
+            None => return Err(IPFSError::UnexpectedResponse("ls returned no objects".to_string()))
+        };
+        let mut entries = parse_links(object)?;
+        // The object's `Hash` is the argument echoed back, which may be a
+        // path rather than a CID.
+        if options.cumulative_sizes {
+            let sizes = self.link_sizes(&self.dag_resolve(&path)?.cid)?;
+            fill_cumulative_sizes(&mut entries, sizes);
+        }
+        Ok(entries)
+    }
+
+    /// Lists the directory at `path` as the daemon reads it, without holding
+    /// the whole listing in memory. Suited to very large, sharded directories.
+    pub fn ls_stream(&self, path: &str, options: &LsOptions) -> Result<LsStream, IPFSError> {
+        let mut args = vec![("arg", self.resolve_path(path)?), ("stream", "true".to_string())];
+        args.extend(options.to_args());
+        Ok(LsStream { lines: ResponseLines::new(self.request("ls", &args)?), pending: VecDeque::new() })
+    }
+
+    /// The names and `Tsize`s of the links below `cid`, reading through the
+    /// shards of a sharded directory. `ls` does not report `Tsize`.
+    fn link_sizes(&self, cid: &Cid) -> Result<Vec<(String, u64)>, IPFSError> {
+        if cid.codec() != Codec::DagPb {
+            return Ok(Vec::new());
+        }
+        let block = self.get_block(cid)?;
+        let mut node = PBNode::new();
+        node.merge_from_bytes(&block)?;
+        let mut content = Data::new();
+        content.merge_from_bytes(node.get_Data())?;
+        if content.get_Type() == Data_DataType::HAMTShard {
+            let entries = ShardedDirectory::new(self, cid)?.entries()?;
+            return Ok(entries.into_iter().map(|entry| (entry.name, entry.tsize)).collect());
+        }
+        Ok(node.get_Links().iter().map(|link| (link.get_Name().to_string(), link.get_Tsize())).collect())
+    }
+}
+
+/// The entries of a streamed listing. The daemon may send several in one
+/// message, so they are queued until read.
+#[cfg(feature = "blocking")]
+pub struct LsStream {
+    lines: ResponseLines,
+    pending: VecDeque<Entry>
+}
+
+#[cfg(feature = "blocking")]
+impl LsStream {
+    /// A handle that ends the listing from another thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.lines.cancel_handle()
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Iterator for LsStream {
+    type Item = Result<Entry, IPFSError>;
+
+    fn next(&mut self) -> Option<Result<Entry, IPFSError>> {
+        loop {
+            if let Some(entry) = self.pending.pop_front() {
+                return Some(Ok(entry));
+            }
+            let json = match self.lines.next()? {
+                Ok(json) => json,
+                Err(err) => return Some(Err(err))
+            };
+            match parse_objects(&json) {
+                Ok(entries) => self.pending.extend(entries),
+                Err(err) => return Some(Err(err))
+            }
+        }
+    }
+}
+
+/// The entries of every object in one message of a streamed listing.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_objects(json: &Value) -> Result<Vec<Entry>, IPFSError> {
+    let mut entries = Vec::new();
+    for object in json.get("Objects").and_then(|objects| objects.as_array()).into_iter().flatten() {
+        entries.extend(parse_links(object)?);
+    }
+    Ok(entries)
+}
+
+/// `ls` reports types with UnixFS type numbers, using 0 (`Raw`) for a type
+/// that was not resolved.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_links(object: &Value) -> Result<Vec<Entry>, IPFSError> {
+    let links = match object.get("Links").and_then(|links| links.as_array()) {
+        Some(links) => links,
+        None => return Ok(Vec::new())
+    };
+    links.iter().map(|link| {
+        let kind = match link.get("Type").and_then(|t| t.as_i64()) {
+            Some(1) | Some(5) => Some(FileType::Directory),
+            Some(2) => Some(FileType::File),
+            Some(4) => Some(FileType::Symlink),
+            _ => None
+        };
+        let attrs = attrs::from_json(link);
+        Ok(Entry {
+            name: string_field(link, "Name")?,
+            cid: string_field(link, "Hash")?.parse()?,
+            cumulative_size: None,
+            kind,
+            size: u64_field(link, "Size").unwrap_or(0),
+            mode: attrs.mode,
+            mtime: attrs.mtime,
+            target: link.get("Target").and_then(|t| t.as_str()).filter(|t| !t.is_empty()).map(|t| t.to_string())
+        })
+    }).collect()
+}
+
+/// Directory entries are matched by name, since a sharded directory may be
+/// listed in any order; the unnamed blocks of a file are matched in order.
+#[cfg(feature = "blocking")]
+fn fill_cumulative_sizes(entries: &mut [Entry], sizes: Vec<(String, u64)>) {
+    if entries.iter().all(|entry| !entry.name.is_empty()) {
+        let by_name: HashMap<String, u64> = sizes.into_iter().collect();
+        for entry in entries {
+            entry.cumulative_size = by_name.get(&entry.name).cloned();
+        }
+    } else if entries.len() == sizes.len() {
+        for (entry, (_, tsize)) in entries.iter_mut().zip(sizes) {
+            entry.cumulative_size = Some(tsize);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+
+    use super::*;
+    use dagpb::DagPbBuilder;
+    use multihash::HashFunction;
+    use serde_json::{self, Value};
+    use tests::mock_daemon;
+
+    #[test]
+    fn links_are_parsed_and_sized() {
+        let json = serde_json::from_str::<Value>(r#"{"Hash":"QmeYYwD4y4DgVVdAzhT7wW5vrvmbKPQj8wcV2pAzjbj886","Links":[
+            {"Name":"index.html","Hash":"QmSXujSW6xykhU5wECQRrSW83YRjz8M8t93mfKddKm9ncL","Size":11812,"Type":2,
+             "Target":"","Mode":420,"ModTime":"2023-11-14T22:13:20Z"},
+            {"Name":"latest","Hash":"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt","Size":0,"Type":4,
+             "Target":"blog/2023"},
+            {"Name":"blog","Hash":"QmTkQCrspeQDEiFBvphH3ULHYNWr3aXpymGiyjMrrUimtZ","Size":0,"Type":0,"Target":""}]}"#).unwrap();
+        let mut entries = parse_links(&json).unwrap();
+        fill_cumulative_sizes(&mut entries, vec![("blog".to_string(), 1717422), ("index.html".to_string(), 11894)]);
+
+        assert_eq!(entries[0].kind, Some(FileType::File));
+        assert_eq!((entries[0].size, entries[0].cumulative_size), (11812, Some(11894)));
+        assert_eq!((entries[0].mode, entries[0].mtime), (Some(0o644), Some(Mtime::new(1_700_000_000, 0))));
+        assert_eq!(entries[1].target, Some("blog/2023".to_string()));
+        assert_eq!(entries[1].cumulative_size, None);
+        assert_eq!((entries[2].kind, entries[2].cumulative_size), (None, Some(1717422)));
+    }
+
+    #[test]
+    fn cumulative_sizes_are_found_for_ipfs_paths() {
+        let file: Cid = "QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".parse().unwrap();
+        let mut content = Data::new();
+        content.set_Type(Data_DataType::Directory);
+        let block = DagPbBuilder::new().link("test.txt", &file, 23).data(content.write_to_bytes().unwrap()).encode();
+        let dir = Cid::for_block(Codec::DagPb, HashFunction::Sha2_256, &block).unwrap().to_v0().unwrap();
+
+        let path = format!("/ipfs/{}", dir);
+        let listing = format!(r#"{{"Objects":[{{"Hash":"{}","Links":[{{"Name":"test.txt","Hash":"{}","Size":15,"Type":2}}]}}]}}"#,
+                              path, file);
+        let resolved = format!(r#"{{"Cid":{{"/":"{}"}},"RemPath":""}}"#, dir);
+        let (server, _) = mock_daemon(move |request| {
+            if request.starts_with("POST /api/v0/ls?") {
+                listing.clone().into_bytes()
+            } else if request.starts_with("POST /api/v0/dag/resolve?") {
+                resolved.clone().into_bytes()
+            } else {
+                block.clone()
+            }
+        });
+        let entries = server.ls(&path, &LsOptions::new().cumulative_sizes(true)).unwrap();
+        assert_eq!((entries[0].size, entries[0].cumulative_size), (15, Some(23)));
+    }
+}
@@ -0,0 +1,365 @@
+//! The Mutable File System: a mutable, path-addressed tree kept by the daemon
+//! on top of immutable UnixFS, reached through `/api/v0/files/*`.
+
+use attrs::Mtime;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use attrs;
+use cid::Cid;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use multipart::Multipart;
+#[cfg(feature = "blocking")]
+use response::read_json;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::{string_field, u64_field};
+#[cfg(feature = "blocking")]
+use response::ResponseReader;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+#[cfg(feature = "blocking")]
+use std::io::Read;
+#[cfg(feature = "blocking")]
+use IPFS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    File,
+    Directory,
+    Symlink
+}
+
+impl FileType {
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn parse(kind: &str) -> Result<FileType, IPFSError> {
+        match kind {
+            "file" => Ok(FileType::File),
+            "directory" => Ok(FileType::Directory),
+            "symlink" => Ok(FileType::Symlink),
+            _ => Err(IPFSError::UnexpectedResponse(format!("unknown file type {:?}", kind)))
+        }
+    }
+}
+
+/// What `files/stat` reports about a path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileStat {
+    pub cid: Cid,
+    pub kind: FileType,
+    /// Size of the file content; zero for directories.
+    pub size: u64,
+    /// Size of the whole DAG below the path, including UnixFS overhead.
+    pub cumulative_size: u64,
+    /// Number of child blocks linked from the root node.
+    pub blocks: u64,
+    /// Permission bits, if the node records them.
+    pub mode: Option<u32>,
+    pub mtime: Option<Mtime>
+}
+
+/// One entry of `files/ls`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MfsEntry {
+    pub name: String,
+    pub kind: FileType,
+    pub size: u64,
+    pub cid: Cid,
+    pub mode: Option<u32>,
+    pub mtime: Option<Mtime>
+}
+
+/// Options for `Mfs::mkdir`.
+#[derive(Debug, Clone, Default)]
+pub struct MkdirOptions {
+    parents: bool,
+    cid_version: Option<u32>,
+    hash: Option<String>
+}
+
+impl MkdirOptions {
+    pub fn new() -> MkdirOptions {
+        MkdirOptions::default()
+    }
+
+    /// Create missing parent directories instead of failing.
+    pub fn parents(mut self, parents: bool) -> MkdirOptions {
+        self.parents = parents;
+        self
+    }
+
+    pub fn cid_version(mut self, version: u32) -> MkdirOptions {
+        self.cid_version = Some(version);
+        self
+    }
+
+    pub fn hash(mut self, hash: &str) -> MkdirOptions {
+        self.hash = Some(hash.to_string());
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = vec![("parents", self.parents.to_string())];
+        push_format_args(&mut args, self.cid_version, &self.hash);
+        args
+    }
+}
+
+/// Options for `Mfs::write`.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    create: bool,
+    parents: bool,
+    truncate: bool,
+    offset: Option<u64>,
+    count: Option<u64>,
+    raw_leaves: Option<bool>,
+    cid_version: Option<u32>,
+    hash: Option<String>
+}
+
+impl WriteOptions {
+    pub fn new() -> WriteOptions {
+        WriteOptions::default()
+    }
+
+    /// Create the file if it does not exist.
+    pub fn create(mut self, create: bool) -> WriteOptions {
+        self.create = create;
+        self
+    }
+
+    /// Create missing parent directories.
+    pub fn parents(mut self, parents: bool) -> WriteOptions {
+        self.parents = parents;
+        self
+    }
+
+    /// Truncate the file to zero length before writing.
+    pub fn truncate(mut self, truncate: bool) -> WriteOptions {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Byte offset to start writing at.
+    pub fn offset(mut self, offset: u64) -> WriteOptions {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Write at most this many bytes of the input.
+    pub fn count(mut self, count: u64) -> WriteOptions {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn raw_leaves(mut self, raw_leaves: bool) -> WriteOptions {
+        self.raw_leaves = Some(raw_leaves);
+        self
+    }
+
+    pub fn cid_version(mut self, version: u32) -> WriteOptions {
+        self.cid_version = Some(version);
+        self
+    }
+
+    pub fn hash(mut self, hash: &str) -> WriteOptions {
+        self.hash = Some(hash.to_string());
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = vec![("create", self.create.to_string()),
+                            ("parents", self.parents.to_string()),
+                            ("truncate", self.truncate.to_string())];
+        if let Some(offset) = self.offset {
+            args.push(("offset", offset.to_string()));
+        }
+        if let Some(count) = self.count {
+            args.push(("count", count.to_string()));
+        }
+        if let Some(raw_leaves) = self.raw_leaves {
+            args.push(("raw-leaves", raw_leaves.to_string()));
+        }
+        push_format_args(&mut args, self.cid_version, &self.hash);
+        args
+    }
+}
+
+/// A handle on the node's MFS, obtained with `IPFS::files`. Paths are
+/// absolute MFS paths such as `/site/index.html`.
+#[cfg(feature = "blocking")]
+pub struct Mfs<'a> {
+    ipfs: &'a IPFS
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    pub fn files<'a>(&'a self) -> Mfs<'a> {
+        Mfs { ipfs: self }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<'a> Mfs<'a> {
+    pub fn mkdir(&self, path: &str, options: &MkdirOptions) -> Result<(), IPFSError> {
+        let mut args = options.to_args();
+        args.push(("arg", path.to_string()));
+        self.ipfs.request("files/mkdir", &args)?;
+        Ok(())
+    }
+
+    /// Writes the contents of `data` into the file at `path`, streaming it to
+    /// the daemon.
+    pub fn write<R: Read>(&self, path: &str, data: R, options: &WriteOptions) -> Result<(), IPFSError> {
+        let mut args = options.to_args();
+        args.push(("arg", path.to_string()));
+        let mut form = Multipart::new();
+        form.add_reader("data", data);
+        self.ipfs.request_with_body("files/write", &args, &mut form)?;
+        Ok(())
+    }
+
+    /// Streams the file at `path`, optionally starting at `offset` and
+    /// stopping after `count` bytes.
+    pub fn read(&self, path: &str, offset: Option<u64>, count: Option<u64>) -> Result<ResponseReader, IPFSError> {
+        let mut args = vec![("arg", path.to_string())];
+        if let Some(offset) = offset {
+            args.push(("offset", offset.to_string()));
+        }
+        if let Some(count) = count {
+            args.push(("count", count.to_string()));
+        }
+        Ok(ResponseReader::new(self.ipfs.request("files/read", &args)?))
+    }
+
+    pub fn mv(&self, from: &str, to: &str) -> Result<(), IPFSError> {
+        self.ipfs.request("files/mv", &[("arg", from.to_string()), ("arg", to.to_string())])?;
+        Ok(())
+    }
+
+    /// Copies `from`, which may be an MFS path or an `/ipfs/` path, to `to`.
+    pub fn cp(&self, from: &str, to: &str, parents: bool) -> Result<(), IPFSError> {
+        let args = [("arg", from.to_string()), ("arg", to.to_string()), ("parents", parents.to_string())];
+        self.ipfs.request("files/cp", &args)?;
+        Ok(())
+    }
+
+    /// Removes `path`. Directories need `recursive`; `force` also ignores
+    /// paths that do not exist.
+    pub fn rm(&self, path: &str, recursive: bool, force: bool) -> Result<(), IPFSError> {
+        let args = [("arg", path.to_string()), ("recursive", recursive.to_string()), ("force", force.to_string())];
+        self.ipfs.request("files/rm", &args)?;
+        Ok(())
+    }
+
+    pub fn stat(&self, path: &str) -> Result<FileStat, IPFSError> {
+        parse_stat(&read_json(self.ipfs.request("files/stat", &[("arg", path.to_string())])?)?)
+    }
+
+    pub fn ls(&self, path: &str) -> Result<Vec<MfsEntry>, IPFSError> {
+        let args = [("arg", path.to_string()), ("long", "true".to_string())];
+        parse_entries(&read_json(self.ipfs.request("files/ls", &args)?)?)
+    }
+
+    /// Writes any changes below `path` out to the blockstore and returns the
+    /// CID of `path`.
+    pub fn flush(&self, path: &str) -> Result<Cid, IPFSError> {
+        cid_field(&read_json(self.ipfs.request("files/flush", &[("arg", path.to_string())])?)?)
+    }
+
+    /// Re-encodes `path` with a different CID version or hash function.
+    pub fn chcid(&self, path: &str, cid_version: Option<u32>, hash: Option<&str>) -> Result<(), IPFSError> {
+        let mut args = vec![("arg", path.to_string())];
+        push_format_args(&mut args, cid_version, &hash.map(|h| h.to_string()));
+        self.ipfs.request("files/chcid", &args)?;
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn push_format_args(args: &mut Vec<(&'static str, String)>, cid_version: Option<u32>, hash: &Option<String>) {
+    if let Some(version) = cid_version {
+        args.push(("cid-version", version.to_string()));
+    }
+    if let Some(ref hash) = *hash {
+        args.push(("hash", hash.clone()));
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_stat(json: &Value) -> Result<FileStat, IPFSError> {
+    let attrs = attrs::from_json(json);
+    Ok(FileStat {
+        cid: string_field(json, "Hash")?.parse()?,
+        kind: FileType::parse(&string_field(json, "Type")?)?,
+        size: u64_field(json, "Size")?,
+        cumulative_size: u64_field(json, "CumulativeSize")?,
+        blocks: u64_field(json, "Blocks")?,
+        mode: attrs.mode,
+        mtime: attrs.mtime
+    })
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_entries(json: &Value) -> Result<Vec<MfsEntry>, IPFSError> {
+    // An empty directory is reported with `"Entries": null`.
+    match json.get("Entries") {
+        Some(Value::Array(entries)) => entries.iter().map(parse_entry).collect(),
+        _ => Ok(Vec::new())
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn cid_field(json: &Value) -> Result<Cid, IPFSError> {
+    string_field(json, "Cid")?.parse()
+}
+
+/// `files/ls` numbers entry types as MFS does, 0 for a file and 1 for a
+/// directory, not with the UnixFS type numbers `ls` uses. Daemons before
+/// UnixFS 1.5 support leave out the mode and mtime.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn parse_entry(json: &Value) -> Result<MfsEntry, IPFSError> {
+    let attrs = attrs::from_json(json);
+    let kind = match json.get("Type").and_then(|t| t.as_u64()) {
+        Some(0) => FileType::File,
+        Some(1) => FileType::Directory,
+        _ => return Err(IPFSError::UnexpectedResponse(format!("unknown MFS entry type in {}", json)))
+    };
+    Ok(MfsEntry {
+        name: string_field(json, "Name")?,
+        kind,
+        size: u64_field(json, "Size")?,
+        cid: string_field(json, "Hash")?.parse()?,
+        mode: attrs.mode,
+        mtime: attrs.mtime
+    })
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+
+    use super::*;
+    use serde_json::{self, Value};
+
+    #[test]
+    fn entries_are_parsed() {
+        let json = serde_json::from_str::<Value>(r#"{"Name":"docs","Type":1,"Size":0,
+                                      "Hash":"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt"}"#).unwrap();
+        let entry = parse_entry(&json).unwrap();
+        assert_eq!(entry.name, "docs");
+        assert_eq!(entry.kind, FileType::Directory);
+        assert_eq!(entry.size, 0);
+    }
+
+    #[test]
+    fn unknown_entry_types_are_rejected() {
+        let file = serde_json::from_str::<Value>(r#"{"Name":"a.txt","Type":0,"Size":5,
+                                     "Hash":"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt"}"#).unwrap();
+        assert_eq!(parse_entry(&file).unwrap().kind, FileType::File);
+        let odd = serde_json::from_str::<Value>(r#"{"Name":"link","Type":4,"Size":0,
+                                    "Hash":"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt"}"#).unwrap();
+        assert!(parse_entry(&odd).is_err());
+    }
+}
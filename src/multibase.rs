@@ -0,0 +1,275 @@
+//! Multibase: a one-character prefix naming the base the rest of the string
+//! is encoded in, so CIDs and keys can be read in whatever form they arrive.
+
+use error::IPFSError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Base {
+    Base2,
+    Base16,
+    Base16Upper,
+    Base32,
+    Base32Upper,
+    Base32Pad,
+    Base32PadUpper,
+    Base36,
+    Base36Upper,
+    Base58Btc,
+    Base64,
+    Base64Pad,
+    Base64Url,
+    Base64UrlPad
+}
+
+const BASE16: &[u8] = b"0123456789abcdef";
+const BASE32: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE36: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+impl Base {
+    pub fn from_prefix(prefix: char) -> Option<Base> {
+        match prefix {
+            '0' => Some(Base::Base2),
+            'f' => Some(Base::Base16),
+            'F' => Some(Base::Base16Upper),
+            'b' => Some(Base::Base32),
+            'B' => Some(Base::Base32Upper),
+            'c' => Some(Base::Base32Pad),
+            'C' => Some(Base::Base32PadUpper),
+            'k' => Some(Base::Base36),
+            'K' => Some(Base::Base36Upper),
+            'z' => Some(Base::Base58Btc),
+            'm' => Some(Base::Base64),
+            'M' => Some(Base::Base64Pad),
+            'u' => Some(Base::Base64Url),
+            'U' => Some(Base::Base64UrlPad),
+            _ => None
+        }
+    }
+
+    pub fn prefix(&self) -> char {
+        match *self {
+            Base::Base2 => '0',
+            Base::Base16 => 'f',
+            Base::Base16Upper => 'F',
+            Base::Base32 => 'b',
+            Base::Base32Upper => 'B',
+            Base::Base32Pad => 'c',
+            Base::Base32PadUpper => 'C',
+            Base::Base36 => 'k',
+            Base::Base36Upper => 'K',
+            Base::Base58Btc => 'z',
+            Base::Base64 => 'm',
+            Base::Base64Pad => 'M',
+            Base::Base64Url => 'u',
+            Base::Base64UrlPad => 'U'
+        }
+    }
+
+    /// Encodes `data` in this base, without the multibase prefix.
+    pub fn encode(&self, data: &[u8]) -> String {
+        match *self {
+            Base::Base2 => encode_bits(data, b"01", 1, 0),
+            Base::Base16 => encode_bits(data, BASE16, 4, 0),
+            Base::Base16Upper => encode_bits(data, BASE16, 4, 0).to_uppercase(),
+            Base::Base32 => encode_bits(data, BASE32, 5, 0),
+            Base::Base32Upper => encode_bits(data, BASE32, 5, 0).to_uppercase(),
+            Base::Base32Pad => encode_bits(data, BASE32, 5, 8),
+            Base::Base32PadUpper => encode_bits(data, BASE32, 5, 8).to_uppercase(),
+            Base::Base36 => encode_radix(data, BASE36),
+            Base::Base36Upper => encode_radix(data, BASE36).to_uppercase(),
+            Base::Base58Btc => encode_radix(data, BASE58),
+            Base::Base64 => encode_bits(data, BASE64, 6, 0),
+            Base::Base64Pad => encode_bits(data, BASE64, 6, 4),
+            Base::Base64Url => encode_bits(data, BASE64_URL, 6, 0),
+            Base::Base64UrlPad => encode_bits(data, BASE64_URL, 6, 4)
+        }
+    }
+
+    /// Decodes `s`, which must not include the multibase prefix. The
+    /// case-insensitive bases (16, 32 and 36) accept either case.
+    pub fn decode(&self, s: &str) -> Result<Vec<u8>, IPFSError> {
+        let decoded = match *self {
+            Base::Base2 => decode_bits(s, b"01", 1, false),
+            Base::Base16 | Base::Base16Upper => decode_bits(&s.to_lowercase(), BASE16, 4, false),
+            Base::Base32 | Base::Base32Upper => decode_bits(&s.to_lowercase(), BASE32, 5, false),
+            Base::Base32Pad | Base::Base32PadUpper => decode_bits(&s.to_lowercase(), BASE32, 5, true),
+            Base::Base36 | Base::Base36Upper => decode_radix(&s.to_lowercase(), BASE36),
+            Base::Base58Btc => decode_radix(s, BASE58),
+            Base::Base64 => decode_bits(s, BASE64, 6, false),
+            Base::Base64Pad => decode_bits(s, BASE64, 6, true),
+            Base::Base64Url => decode_bits(s, BASE64_URL, 6, false),
+            Base::Base64UrlPad => decode_bits(s, BASE64_URL, 6, true)
+        };
+        decoded.ok_or_else(|| IPFSError::InvalidMultibase(format!("{}{}", self.prefix(), s)))
+    }
+}
+
+/// Encodes `data` with its multibase prefix.
+pub fn encode(base: Base, data: &[u8]) -> String {
+    let mut out = String::new();
+    out.push(base.prefix());
+    out.push_str(&base.encode(data));
+    out
+}
+
+/// Decodes a prefixed multibase string, returning the base it was in.
+pub fn decode(s: &str) -> Result<(Base, Vec<u8>), IPFSError> {
+    let prefix = match s.chars().next() {
+        Some(prefix) => prefix,
+        None => return Err(IPFSError::InvalidMultibase(String::new()))
+    };
+    match Base::from_prefix(prefix) {
+        Some(base) => Ok((base, base.decode(&s[prefix.len_utf8()..])?)),
+        None => Err(IPFSError::InvalidMultibase(s.to_string()))
+    }
+}
+
+/// RFC 4648 style encoding for bases that are a power of two, optionally
+/// padded with `=` to a multiple of `pad_to` characters.
+fn encode_bits(data: &[u8], alphabet: &[u8], bits_per_char: u32, pad_to: usize) -> String {
+    let mask = (1u32 << bits_per_char) - 1;
+    let mut out = String::with_capacity((data.len() * 8).div_ceil(bits_per_char as usize));
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= bits_per_char {
+            bits -= bits_per_char;
+            out.push(alphabet[((buffer >> bits) & mask) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        out.push(alphabet[((buffer << (bits_per_char - bits)) & mask) as usize] as char);
+    }
+    if pad_to > 0 {
+        while !out.len().is_multiple_of(pad_to) {
+            out.push('=');
+        }
+    }
+    out
+}
+
+fn decode_bits(s: &str, alphabet: &[u8], bits_per_char: u32, padded: bool) -> Option<Vec<u8>> {
+    let s = if padded { s.trim_end_matches('=') } else { s };
+    let mut out = Vec::with_capacity(s.len() * bits_per_char as usize / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let value = alphabet.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << bits_per_char) | value;
+        bits += bits_per_char;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    // Anything left over must be zero padding bits from the final character.
+    if buffer != 0 || bits >= bits_per_char {
+        return None;
+    }
+    Some(out)
+}
+
+/// Big-number encoding for bases that are not a power of two. Leading zero
+/// bytes are kept as leading zero digits, as in Bitcoin's base58.
+fn encode_radix(data: &[u8], alphabet: &[u8]) -> String {
+    let radix = alphabet.len() as u32;
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 2);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % radix) as u8;
+            carry /= radix;
+        }
+        while carry > 0 {
+            digits.push((carry % radix) as u8);
+            carry /= radix;
+        }
+    }
+    let mut out = String::with_capacity(zeros + digits.len());
+    for _ in 0..zeros {
+        out.push(alphabet[0] as char);
+    }
+    for &digit in digits.iter().rev() {
+        out.push(alphabet[digit as usize] as char);
+    }
+    out
+}
+
+fn decode_radix(s: &str, alphabet: &[u8]) -> Option<Vec<u8>> {
+    let radix = alphabet.len() as u32;
+    let zeros = s.bytes().take_while(|&c| c == alphabet[0]).count();
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len());
+    for c in s.bytes().skip(zeros) {
+        let mut carry = alphabet.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * radix;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut out = vec![0; zeros];
+    out.extend(bytes.iter().rev());
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Test vectors from the multibase specification.
+    #[test]
+    fn encodes_spec_vectors() {
+        let data = b"yes mani !";
+        let cases = [
+            (Base::Base2, "001111001011001010111001100100000011011010110000101101110011010010010000000100001"),
+            (Base::Base16, "f796573206d616e692021"),
+            (Base::Base16Upper, "F796573206D616E692021"),
+            (Base::Base32, "bpfsxgidnmfxgsibb"),
+            (Base::Base32Upper, "BPFSXGIDNMFXGSIBB"),
+            (Base::Base32Pad, "cpfsxgidnmfxgsibb"),
+            (Base::Base32PadUpper, "CPFSXGIDNMFXGSIBB"),
+            (Base::Base36, "k2lcpzo5yikidynfl"),
+            (Base::Base36Upper, "K2LCPZO5YIKIDYNFL"),
+            (Base::Base58Btc, "z7paNL19xttacUY"),
+            (Base::Base64, "meWVzIG1hbmkgIQ"),
+            (Base::Base64Pad, "MeWVzIG1hbmkgIQ=="),
+            (Base::Base64Url, "ueWVzIG1hbmkgIQ"),
+            (Base::Base64UrlPad, "UeWVzIG1hbmkgIQ=="),
+        ];
+        for &(base, expected) in &cases {
+            assert_eq!(encode(base, data), expected);
+            assert_eq!(decode(expected).unwrap(), (base, data.to_vec()));
+        }
+    }
+
+    #[test]
+    fn leading_zeros_are_preserved() {
+        let data = b"\x00\x00yes mani !";
+        assert_eq!(encode(Base::Base58Btc, data), "z117paNL19xttacUY");
+        assert_eq!(encode(Base::Base36, data), "k002lcpzo5yikidynfl");
+        assert_eq!(decode("z117paNL19xttacUY").unwrap().1, data.to_vec());
+        assert_eq!(decode("k002lcpzo5yikidynfl").unwrap().1, data.to_vec());
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        assert!(decode("").is_err());
+        assert!(decode("x123").is_err());
+        assert!(decode("z0OIl").is_err());
+        assert!(decode("b1").is_err());
+    }
+}
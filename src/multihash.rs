@@ -0,0 +1,308 @@
+//! Self-describing hashes: `<varint function code><varint digest length><digest>`.
+
+use blake2::digest::{Update, VariableOutput};
+use blake2::{Blake2bVar, Blake2sVar};
+use error::IPFSError;
+use sha2::{Digest, Sha256, Sha512};
+use sha3::{Keccak224, Keccak256, Keccak384, Keccak512, Sha3_224, Sha3_256, Sha3_384, Sha3_512};
+use std::fmt;
+use varint;
+
+/// The shortest digest a non-identity multihash may have. Kubo refuses to
+/// fetch or verify content by anything shorter, since a short enough
+/// truncation matches almost any data.
+const MIN_DIGEST_LEN: usize = 20;
+
+/// The hash functions IPFS content is commonly addressed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashFunction {
+    /// The "digest" is the data itself; used to inline tiny blocks into CIDs.
+    Identity,
+    Sha2_256,
+    Sha2_512,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    Keccak224,
+    Keccak256,
+    Keccak384,
+    Keccak512,
+    /// BLAKE2b with the given digest size in bytes (1 to 64).
+    Blake2b(u8),
+    /// BLAKE2s with the given digest size in bytes (1 to 32).
+    Blake2s(u8)
+}
+
+impl HashFunction {
+    pub fn from_code(code: u64) -> Option<HashFunction> {
+        match code {
+            0x00 => Some(HashFunction::Identity),
+            0x12 => Some(HashFunction::Sha2_256),
+            0x13 => Some(HashFunction::Sha2_512),
+            0x14 => Some(HashFunction::Sha3_512),
+            0x15 => Some(HashFunction::Sha3_384),
+            0x16 => Some(HashFunction::Sha3_256),
+            0x17 => Some(HashFunction::Sha3_224),
+            0x1a => Some(HashFunction::Keccak224),
+            0x1b => Some(HashFunction::Keccak256),
+            0x1c => Some(HashFunction::Keccak384),
+            0x1d => Some(HashFunction::Keccak512),
+            0xb201..=0xb240 => Some(HashFunction::Blake2b((code - 0xb200) as u8)),
+            0xb241..=0xb260 => Some(HashFunction::Blake2s((code - 0xb240) as u8)),
+            _ => None
+        }
+    }
+
+    pub fn code(&self) -> u64 {
+        match *self {
+            HashFunction::Identity => 0x00,
+            HashFunction::Sha2_256 => 0x12,
+            HashFunction::Sha2_512 => 0x13,
+            HashFunction::Sha3_512 => 0x14,
+            HashFunction::Sha3_384 => 0x15,
+            HashFunction::Sha3_256 => 0x16,
+            HashFunction::Sha3_224 => 0x17,
+            HashFunction::Keccak224 => 0x1a,
+            HashFunction::Keccak256 => 0x1b,
+            HashFunction::Keccak384 => 0x1c,
+            HashFunction::Keccak512 => 0x1d,
+            HashFunction::Blake2b(len) => 0xb200 + len as u64,
+            HashFunction::Blake2s(len) => 0xb240 + len as u64
+        }
+    }
+
+    /// The name the daemon uses for this function, e.g. for `add --hash`.
+    pub fn name(&self) -> String {
+        match *self {
+            HashFunction::Identity => "identity".to_string(),
+            HashFunction::Sha2_256 => "sha2-256".to_string(),
+            HashFunction::Sha2_512 => "sha2-512".to_string(),
+            HashFunction::Sha3_224 => "sha3-224".to_string(),
+            HashFunction::Sha3_256 => "sha3-256".to_string(),
+            HashFunction::Sha3_384 => "sha3-384".to_string(),
+            HashFunction::Sha3_512 => "sha3-512".to_string(),
+            HashFunction::Keccak224 => "keccak-224".to_string(),
+            HashFunction::Keccak256 => "keccak-256".to_string(),
+            HashFunction::Keccak384 => "keccak-384".to_string(),
+            HashFunction::Keccak512 => "keccak-512".to_string(),
+            HashFunction::Blake2b(len) => format!("blake2b-{}", len as u32 * 8),
+            HashFunction::Blake2s(len) => format!("blake2s-{}", len as u32 * 8)
+        }
+    }
+
+    /// The length of the function's full digest, or `None` for a BLAKE2
+    /// size the function does not have, such as `Blake2b(0)` or
+    /// `Blake2s(33)`. Identity digests are as long as the data.
+    pub fn digest_len(&self) -> Option<usize> {
+        match *self {
+            HashFunction::Identity => Some(usize::MAX),
+            HashFunction::Sha3_224 | HashFunction::Keccak224 => Some(28),
+            HashFunction::Sha2_256 | HashFunction::Sha3_256 | HashFunction::Keccak256 => Some(32),
+            HashFunction::Sha3_384 | HashFunction::Keccak384 => Some(48),
+            HashFunction::Sha2_512 | HashFunction::Sha3_512 | HashFunction::Keccak512 => Some(64),
+            HashFunction::Blake2b(len) if (1..=64).contains(&len) => Some(len as usize),
+            HashFunction::Blake2s(len) if (1..=32).contains(&len) => Some(len as usize),
+            HashFunction::Blake2b(_) | HashFunction::Blake2s(_) => None
+        }
+    }
+
+    /// Hashes `data`, producing the function's full-length digest.
+    pub fn digest(&self, data: &[u8]) -> Result<Vec<u8>, IPFSError> {
+        let len = match self.digest_len() {
+            Some(len) => len,
+            None => return Err(IPFSError::InvalidHashFunction(self.name()))
+        };
+        Ok(match *self {
+            HashFunction::Identity => data.to_vec(),
+            HashFunction::Sha2_256 => Sha256::digest(data).to_vec(),
+            HashFunction::Sha2_512 => Sha512::digest(data).to_vec(),
+            HashFunction::Sha3_224 => Sha3_224::digest(data).to_vec(),
+            HashFunction::Sha3_256 => Sha3_256::digest(data).to_vec(),
+            HashFunction::Sha3_384 => Sha3_384::digest(data).to_vec(),
+            HashFunction::Sha3_512 => Sha3_512::digest(data).to_vec(),
+            HashFunction::Keccak224 => Keccak224::digest(data).to_vec(),
+            HashFunction::Keccak256 => Keccak256::digest(data).to_vec(),
+            HashFunction::Keccak384 => Keccak384::digest(data).to_vec(),
+            HashFunction::Keccak512 => Keccak512::digest(data).to_vec(),
+            HashFunction::Blake2b(_) => variable(Blake2bVar::new(len), data),
+            HashFunction::Blake2s(_) => variable(Blake2sVar::new(len), data)
+        })
+    }
+}
+
+/// Runs a BLAKE2 hasher whose output size `digest_len` has already checked.
+fn variable<H: Update + VariableOutput, E>(hasher: Result<H, E>, data: &[u8]) -> Vec<u8> {
+    let mut hasher = hasher.ok().expect("digest_len only allows valid BLAKE2 sizes");
+    hasher.update(data);
+    let mut out = vec![0; hasher.output_size()];
+    hasher.finalize_variable(&mut out).expect("the buffer is the output size");
+    out
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Multihash {
+    code: u64,
+    digest: Vec<u8>
+}
+
+impl Multihash {
+    /// Hashes `data` with `function`.
+    pub fn new(function: HashFunction, data: &[u8]) -> Result<Multihash, IPFSError> {
+        Ok(Multihash { code: function.code(), digest: function.digest(data)? })
+    }
+
+    /// Decodes a multihash, checking that the declared digest length matches
+    /// the bytes that follow it. A known function's digest may be truncated,
+    /// but not empty and not longer than the function produces.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Multihash, IPFSError> {
+        let invalid = || IPFSError::InvalidMultihash(bytes.to_vec());
+        let (code, code_len) = varint::decode(bytes).ok_or_else(invalid)?;
+        let (len, len_len) = varint::decode(&bytes[code_len..]).ok_or_else(invalid)?;
+        let digest = &bytes[code_len + len_len..];
+        if digest.len() as u64 != len {
+            return Err(invalid());
+        }
+        match HashFunction::from_code(code) {
+            Some(HashFunction::Identity) | None => {}
+            Some(function) if digest.is_empty() || Some(digest.len()) > function.digest_len() => return Err(invalid()),
+            Some(_) => {}
+        }
+        Ok(Multihash { code, digest: digest.to_vec() })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.digest.len() + 4);
+        varint::encode(self.code, &mut bytes);
+        varint::encode(self.digest.len() as u64, &mut bytes);
+        bytes.extend_from_slice(&self.digest);
+        bytes
+    }
+
+    pub fn code(&self) -> u64 {
+        self.code
+    }
+
+    /// The hash function, if it is one this crate knows.
+    pub fn function(&self) -> Option<HashFunction> {
+        HashFunction::from_code(self.code)
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Checks that `data` hashes to this multihash. Digests shorter than the
+    /// function's output are treated as truncated, as go-multihash allows,
+    /// but like kubo this refuses ones under 20 bytes, which prove little.
+    pub fn verify(&self, data: &[u8]) -> Result<(), IPFSError> {
+        let function = match self.function() {
+            Some(function) => function,
+            None => return Err(IPFSError::UnsupportedHash(self.code))
+        };
+        if function != HashFunction::Identity && self.digest.len() < MIN_DIGEST_LEN {
+            return Err(IPFSError::InvalidMultihash(self.to_bytes()));
+        }
+        let computed = function.digest(data)?;
+        let matches = match function {
+            HashFunction::Identity => computed == self.digest,
+            _ => computed.len() >= self.digest.len() && computed[..self.digest.len()] == self.digest[..]
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(IPFSError::HashMismatch(self.clone()))
+        }
+    }
+}
+
+impl fmt::Debug for Multihash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.function().map(|function| function.name()).unwrap_or_else(|| format!("0x{:x}", self.code));
+        write!(f, "Multihash({}:", name)?;
+        for byte in &self.digest {
+            write!(f, "{:02x}", byte)?;
+        }
+        f.write_str(")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn known_digests() {
+        let cases = [
+            (HashFunction::Sha2_256, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"),
+            (HashFunction::Sha3_256, "3338be694f50c5f338814986cdf0686453a888b84f424d792af4b9202398f392"),
+            (HashFunction::Blake2b(32), "324dcf027dd4a30a932c441f365a25e86b173defa4b8e58948253471b81b72cf"),
+            (HashFunction::Blake2s(32), "19213bacc58dee6dbde3ceb9a47cbb330b3d86f8cca8997eb00be456f140ca25"),
+        ];
+        for &(function, expected) in &cases {
+            assert_eq!(hex(&function.digest(b"hello").unwrap()), expected, "{}", function.name());
+        }
+    }
+
+    #[test]
+    fn round_trips_and_verifies() {
+        let hash = Multihash::new(HashFunction::Blake2b(32), b"hello").unwrap();
+        let bytes = hash.to_bytes();
+        assert_eq!(&bytes[..3], &[0xa0, 0xe4, 0x02]);
+        assert_eq!(bytes[3], 32);
+        let decoded = Multihash::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, hash);
+        assert!(decoded.verify(b"hello").is_ok());
+        assert!(decoded.verify(b"hellO").is_err());
+    }
+
+    #[test]
+    fn identity_holds_the_data() {
+        let hash = Multihash::new(HashFunction::Identity, b"tiny").unwrap();
+        assert_eq!(hash.to_bytes(), b"\x00\x04tiny".to_vec());
+        assert!(hash.verify(b"tiny").is_ok());
+        assert!(hash.verify(b"tin").is_err());
+    }
+
+    #[test]
+    fn unknown_functions_cannot_be_verified() {
+        let hash = Multihash::from_bytes(&[0x99, 0x01, 0x01, 0xff]).unwrap();
+        match hash.verify(b"x") {
+            Err(IPFSError::UnsupportedHash(0x99)) => {}
+            other => panic!("unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn short_digests_do_not_verify() {
+        assert!(Multihash::from_bytes(&[0x12, 0x00]).is_err());
+        let full = Multihash::new(HashFunction::Sha2_256, b"hello").unwrap().to_bytes();
+        let mut truncated = vec![0x12, 4];
+        truncated.extend_from_slice(&full[2..6]);
+        assert!(Multihash::from_bytes(&truncated).unwrap().verify(b"hello").is_err());
+        let mut truncated = vec![0x12, 20];
+        truncated.extend_from_slice(&full[2..22]);
+        assert!(Multihash::from_bytes(&truncated).unwrap().verify(b"hello").is_ok());
+        let mut long = full.clone();
+        long[1] = 33;
+        long.push(0);
+        assert!(Multihash::from_bytes(&long).is_err());
+    }
+
+    #[test]
+    fn blake2_sizes_are_checked() {
+        for &function in &[HashFunction::Blake2b(0), HashFunction::Blake2b(65), HashFunction::Blake2s(0), HashFunction::Blake2s(33)] {
+            match Multihash::new(function, b"hello") {
+                Err(IPFSError::InvalidHashFunction(_)) => {}
+                other => panic!("unexpected result for {}: {:?}", function.name(), other)
+            }
+        }
+        assert_eq!(HashFunction::Blake2s(1).digest(b"hello").unwrap().len(), 1);
+        assert_eq!(HashFunction::Blake2b(64).digest(b"hello").unwrap().len(), 64);
+    }
+}
@@ -0,0 +1,273 @@
+//! A streaming `multipart/form-data` body in the form the daemon expects for
+//! `add`: one part per file, directory or symlink, named by its
+//! URL-encoded path relative to the root of the upload. A part's UnixFS
+//! mode and modification time, if any, travel as extra parameters of its
+//! `Content-Disposition`.
+
+use attrs::FileAttrs;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Cursor, Read};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "blocking")]
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::form_urlencoded;
+
+static BOUNDARY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+enum Content<'a> {
+    Reader(Box<dyn Read + 'a>),
+    /// A local file, only opened once the body reaches it so that large
+    /// directory trees do not hold every file open at once.
+    File(PathBuf),
+    Directory,
+    Symlink(String)
+}
+
+struct Part<'a> {
+    name: String,
+    content: Content<'a>,
+    attrs: FileAttrs
+}
+
+pub struct Multipart<'a> {
+    boundary: String,
+    parts: VecDeque<Part<'a>>,
+    current: Option<Box<dyn Read + 'a>>,
+    pending: Cursor<Vec<u8>>,
+    finished: bool
+}
+
+impl<'a> Multipart<'a> {
+    pub fn new() -> Multipart<'a> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+        let count = BOUNDARY_COUNTER.fetch_add(1, Ordering::Relaxed);
+        Multipart {
+            boundary: format!("rust-ipfs-api-{:08x}{:04x}", nanos, count),
+            parts: VecDeque::new(),
+            current: None,
+            pending: Cursor::new(Vec::new()),
+            finished: false
+        }
+    }
+
+    pub fn add_reader<R: Read + 'a>(&mut self, name: &str, reader: R) {
+        self.push(name, Content::Reader(Box::new(reader)));
+    }
+
+    pub fn add_file(&mut self, name: &str, path: PathBuf) {
+        self.push(name, Content::File(path));
+    }
+
+    pub fn add_directory(&mut self, name: &str) {
+        self.push(name, Content::Directory);
+    }
+
+    pub fn add_symlink(&mut self, name: &str, target: &str) {
+        self.push(name, Content::Symlink(target.to_string()));
+    }
+
+    /// Sets the mode and modification time of the part added last.
+    pub fn set_attrs(&mut self, attrs: FileAttrs) {
+        if let Some(part) = self.parts.back_mut() {
+            part.attrs = attrs;
+        }
+    }
+
+    fn push(&mut self, name: &str, content: Content<'a>) {
+        self.parts.push_back(Part { name: name.to_string(), content, attrs: FileAttrs::default() });
+    }
+
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Queues the header of the next part and makes its content current.
+    /// Returns false once every part has been started.
+    fn start_next_part(&mut self) -> io::Result<bool> {
+        let part = match self.parts.pop_front() {
+            Some(part) => part,
+            None => return Ok(false)
+        };
+        let (content_type, reader): (&str, Option<Box<dyn Read + 'a>>) = match part.content {
+            Content::Reader(reader) => ("application/octet-stream", Some(reader)),
+            Content::File(path) => ("application/octet-stream", Some(Box::new(File::open(path)?))),
+            Content::Directory => ("application/x-directory", None),
+            Content::Symlink(target) => ("application/symlink", Some(Box::new(Cursor::new(target.into_bytes()))))
+        };
+        let filename: String = form_urlencoded::byte_serialize(part.name.as_bytes()).collect();
+        let mut disposition = format!("form-data; name=\"file\"; filename=\"{}\"", filename);
+        if let Some(mode) = part.attrs.mode {
+            disposition.push_str(&format!("; mode={:04o}", mode));
+        }
+        if let Some(mtime) = part.attrs.mtime {
+            disposition.push_str(&format!("; mtime={}", mtime.seconds));
+            if mtime.nanos != 0 {
+                disposition.push_str(&format!("; mtime-nsecs={}", mtime.nanos));
+            }
+        }
+        let header = format!("--{}\r\nContent-Disposition: {}\r\nContent-Type: {}\r\n\r\n",
+                             self.boundary, disposition, content_type);
+        self.pending = Cursor::new(header.into_bytes());
+        self.current = reader;
+        if self.current.is_none() {
+            self.pending.get_mut().extend_from_slice(b"\r\n");
+        }
+        Ok(true)
+    }
+}
+
+impl<'a> Read for Multipart<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.pending.read(buf)?;
+            if n > 0 {
+                return Ok(n);
+            }
+
+            if let Some(mut reader) = self.current.take() {
+                let n = reader.read(buf)?;
+                if n > 0 {
+                    self.current = Some(reader);
+                    return Ok(n);
+                }
+                self.pending = Cursor::new(b"\r\n".to_vec());
+                continue;
+            }
+
+            if self.start_next_part()? {
+                continue;
+            }
+
+            if self.finished {
+                return Ok(0);
+            }
+            self.finished = true;
+            self.pending = Cursor::new(format!("--{}--\r\n", self.boundary).into_bytes());
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[cfg(feature = "blocking")]
+impl<'a> Multipart<'a> {
+    /// Reads the whole body into `sender`, for a `Chunks` on another thread.
+    /// Stops early, without error, if the receiving end hangs up. A failed
+    /// read is passed on too, so that the body ends in an error rather than
+    /// looking complete.
+    pub fn write_chunks(&mut self, sender: &SyncSender<io::Result<Vec<u8>>>) -> io::Result<()> {
+        let mut buf = vec![0; CHUNK_SIZE];
+        loop {
+            match self.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(n) => if sender.send(Ok(buf[..n].to_vec())).is_err() {
+                    return Ok(());
+                },
+                Err(err) => {
+                    let _ = sender.send(Err(io::Error::new(err.kind(), err.to_string())));
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+/// A body written by `Multipart::write_chunks` on another thread. Unlike the
+/// form itself, it can be handed to an HTTP client that needs a `Send +
+/// 'static` body.
+#[cfg(feature = "blocking")]
+pub struct Chunks {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    current: Cursor<Vec<u8>>
+}
+
+#[cfg(feature = "blocking")]
+impl Chunks {
+    pub fn new(receiver: Receiver<io::Result<Vec<u8>>>) -> Chunks {
+        Chunks { receiver, current: Cursor::new(Vec::new()) }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.current.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            match self.receiver.recv() {
+                Ok(Ok(chunk)) => self.current = Cursor::new(chunk),
+                Ok(Err(err)) => return Err(err),
+                Err(_) => return Ok(0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::io::Read;
+    #[cfg(feature = "blocking")]
+    use std::sync::mpsc;
+    #[cfg(feature = "blocking")]
+    use std::thread;
+
+    #[test]
+    fn parts_are_framed_by_the_boundary() {
+        let mut form = Multipart::new();
+        form.add_directory("docs");
+        form.add_reader("docs/a b.txt", &b"hello"[..]);
+        let boundary = form.boundary.clone();
+
+        let mut body = String::new();
+        form.read_to_string(&mut body).unwrap();
+
+        let expected = format!("--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"docs\"\r\n\
+                                Content-Type: application/x-directory\r\n\r\n\r\n\
+                                --{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"docs%2Fa+b.txt\"\r\n\
+                                Content-Type: application/octet-stream\r\n\r\nhello\r\n\
+                                --{b}--\r\n", b = boundary);
+        assert_eq!(body, expected);
+    }
+
+    #[test]
+    fn attrs_extend_the_disposition() {
+        let mut form = Multipart::new();
+        form.add_reader("a.txt", &b""[..]);
+        form.set_attrs(FileAttrs { mode: Some(0o644), mtime: Some(::attrs::Mtime::new(1_700_000_000, 0)) });
+
+        let mut body = String::new();
+        form.read_to_string(&mut body).unwrap();
+        assert!(body.contains("Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"; mode=0644; \
+                               mtime=1700000000\r\n"));
+    }
+
+    #[cfg(feature = "blocking")]
+    struct Failing;
+
+    #[cfg(feature = "blocking")]
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk gone"))
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn read_errors_reach_the_sending_thread() {
+        let mut form = Multipart::new();
+        form.add_reader("a.txt", Failing);
+        let (sender, receiver) = mpsc::sync_channel(4);
+        let sending = thread::spawn(move || Chunks::new(receiver).read_to_end(&mut Vec::new()));
+        assert!(form.write_chunks(&sender).is_err());
+        drop(sender);
+        assert_eq!(sending.join().unwrap().unwrap_err().kind(), io::ErrorKind::Other);
+    }
+}
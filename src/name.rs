@@ -0,0 +1,201 @@
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use response::read_json;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::string_field;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+use std::time::Duration;
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// Options for `name_publish`. Anything left unset uses the daemon's default.
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+    key: Option<String>,
+    lifetime: Option<Duration>,
+    ttl: Option<Duration>,
+    resolve: Option<bool>,
+    allow_offline: bool
+}
+
+impl PublishOptions {
+    pub fn new() -> PublishOptions {
+        PublishOptions::default()
+    }
+
+    /// Name of the key to publish under, as listed by `key_list`. Defaults to
+    /// the node's own key, `self`.
+    pub fn key(mut self, key: &str) -> PublishOptions {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    /// How long the record stays valid.
+    pub fn lifetime(mut self, lifetime: Duration) -> PublishOptions {
+        self.lifetime = Some(lifetime);
+        self
+    }
+
+    /// How long resolvers may cache the record.
+    pub fn ttl(mut self, ttl: Duration) -> PublishOptions {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Whether the daemon checks that the path resolves before publishing.
+    pub fn resolve(mut self, resolve: bool) -> PublishOptions {
+        self.resolve = Some(resolve);
+        self
+    }
+
+    pub fn allow_offline(mut self, allow_offline: bool) -> PublishOptions {
+        self.allow_offline = allow_offline;
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = Vec::new();
+        if let Some(ref key) = self.key {
+            args.push(("key", key.clone()));
+        }
+        if let Some(lifetime) = self.lifetime {
+            args.push(("lifetime", go_duration(lifetime)));
+        }
+        if let Some(ttl) = self.ttl {
+            args.push(("ttl", go_duration(ttl)));
+        }
+        if let Some(resolve) = self.resolve {
+            args.push(("resolve", resolve.to_string()));
+        }
+        if self.allow_offline {
+            args.push(("allow-offline", "true".to_string()));
+        }
+        args
+    }
+}
+
+/// Options for `name_resolve`.
+#[derive(Debug, Clone)]
+pub struct ResolveOptions {
+    recursive: bool,
+    nocache: bool
+}
+
+impl Default for ResolveOptions {
+    fn default() -> ResolveOptions {
+        ResolveOptions { recursive: true, nocache: false }
+    }
+}
+
+impl ResolveOptions {
+    pub fn new() -> ResolveOptions {
+        ResolveOptions::default()
+    }
+
+    /// Keep resolving until the result is no longer an IPNS name. On by
+    /// default.
+    pub fn recursive(mut self, recursive: bool) -> ResolveOptions {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Bypass the daemon's cache of resolved names.
+    pub fn nocache(mut self, nocache: bool) -> ResolveOptions {
+        self.nocache = nocache;
+        self
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(&self) -> Vec<(&'static str, String)> {
+        vec![("recursive", self.recursive.to_string()), ("nocache", self.nocache.to_string())]
+    }
+}
+
+/// A published IPNS record: `name` (the key's ID) now points at `value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameEntry {
+    pub name: String,
+    pub value: String
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Publishes `path` (e.g. `/ipfs/Qm...`) under an IPNS name.
+    pub fn name_publish(&self, path: &str, options: &PublishOptions) -> Result<NameEntry, IPFSError> {
+        let mut args = options.to_args();
+        args.push(("arg", path.to_string()));
+        parse_entry(&read_json(self.request("name/publish", &args)?)?)
+    }
+
+    /// Resolves an IPNS name (`/ipns/k51...`, a bare key ID or a DNSLink
+    /// domain) to the path it points at.
+    pub fn name_resolve(&self, name: &str, options: &ResolveOptions) -> Result<String, IPFSError> {
+        let mut args = vec![("arg", name.to_string())];
+        args.extend(options.to_args());
+        string_field(&read_json(self.request("name/resolve", &args)?)?, "Path")
+    }
+
+    /// Resolves a leading `/ipns/` name so that the rest of the client only
+    /// ever sees immutable `/ipfs/` paths. Other paths are returned as is.
+    pub fn resolve_path(&self, path: &str) -> Result<String, IPFSError> {
+        let (name, subpath) = match split_ipns(path) {
+            Some(split) => split,
+            None => return Ok(path.to_string())
+        };
+        let resolved = self.name_resolve(name, &ResolveOptions::new())?;
+        Ok(format!("{}{}", resolved, subpath))
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_entry(json: &Value) -> Result<NameEntry, IPFSError> {
+    Ok(NameEntry { name: string_field(json, "Name")?, value: string_field(json, "Value")? })
+}
+
+/// Splits an `/ipns/<name>/<subpath>` path into the name and the rest of the
+/// path, which keeps its leading slash.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn split_ipns(path: &str) -> Option<(&str, &str)> {
+    if !path.starts_with("/ipns/") {
+        return None;
+    }
+    let rest = &path["/ipns/".len()..];
+    Some(match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "")
+    })
+}
+
+/// Formats a duration the way Go's `time.ParseDuration` reads it, in the
+/// largest unit that keeps it exact.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn go_duration(duration: Duration) -> String {
+    let nanos = duration.subsec_nanos();
+    if nanos == 0 {
+        format!("{}s", duration.as_secs())
+    } else if nanos.is_multiple_of(1_000_000) {
+        format!("{}ms", duration.as_millis())
+    } else if nanos.is_multiple_of(1_000) {
+        format!("{}us", duration.as_micros())
+    } else {
+        format!("{}ns", duration.as_nanos())
+    }
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn durations_are_formatted_for_go() {
+        assert_eq!(go_duration(Duration::from_secs(86400)), "86400s");
+        assert_eq!(go_duration(Duration::from_millis(1500)), "1500ms");
+        assert_eq!(go_duration(Duration::from_micros(250)), "250us");
+        assert_eq!(go_duration(Duration::new(1, 5)), "1000000005ns");
+    }
+}
@@ -0,0 +1,283 @@
+use cid::Cid;
+#[cfg(feature = "blocking")]
+use dagpb::encode_dag_pb;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use merkledag::PBNode;
+#[cfg(feature = "blocking")]
+use multipart::Multipart;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use protobuf::Message;
+#[cfg(feature = "blocking")]
+use response::read_json;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::{string_field, u64_field};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+#[cfg(feature = "blocking")]
+use std::io::Read;
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// Starting points for `object_new`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectTemplate {
+    /// A node with no data and no links.
+    Empty,
+    /// An empty UnixFS directory.
+    UnixfsDir
+}
+
+impl ObjectTemplate {
+    /// The empty node is what `object/new` makes without a template argument.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_args(self) -> Vec<(&'static str, String)> {
+        match self {
+            ObjectTemplate::Empty => Vec::new(),
+            ObjectTemplate::UnixfsDir => vec![("arg", "unixfs-dir".to_string())]
+        }
+    }
+}
+
+/// Sizes reported by `object_stat`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectStat {
+    pub cid: Cid,
+    pub num_links: u64,
+    /// Size of the encoded node.
+    pub block_size: u64,
+    /// Size of the encoded links.
+    pub links_size: u64,
+    pub data_size: u64,
+    /// Size of the node and everything below it.
+    pub cumulative_size: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectLink {
+    pub name: String,
+    pub cid: Cid,
+    /// Cumulative size of the target.
+    pub size: u64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeType {
+    Add,
+    Remove,
+    Modify
+}
+
+/// One difference found by `object_diff`. `before` is unset for additions
+/// and `after` for removals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectChange {
+    pub kind: ChangeType,
+    pub path: String,
+    pub before: Option<Cid>,
+    pub after: Option<Cid>
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Fetches the dag-pb node at `path`.
+    pub fn object_get(&self, path: &str) -> Result<PBNode, IPFSError> {
+        self.call("object/get", vec![self.resolve_path(path)?])
+    }
+
+    pub fn object_new(&self, template: ObjectTemplate) -> Result<Cid, IPFSError> {
+        hash_field(&read_json(self.request("object/new", &template.to_args())?)?)
+    }
+
+    /// Stores `node` and returns its CID, which is checked against the bytes
+    /// that were sent. The node is sent in canonical form, with its links
+    /// sorted, as the daemon would store it anyway.
+    pub fn object_put(&self, node: &PBNode) -> Result<Cid, IPFSError> {
+        let block = encode_dag_pb(node)?;
+        let mut form = Multipart::new();
+        form.add_reader("node", &block[..]);
+        let res = self.request_with_body("object/put", &[("inputenc", "protobuf".to_string())], &mut form)?;
+        let cid = hash_field(&read_json(res)?)?;
+        cid.verify(&block)?;
+        Ok(cid)
+    }
+
+    pub fn object_stat(&self, path: &str) -> Result<ObjectStat, IPFSError> {
+        parse_stat(&read_json(self.request("object/stat", &[("arg", self.resolve_path(path)?)])?)?)
+    }
+
+    pub fn object_links(&self, path: &str) -> Result<Vec<ObjectLink>, IPFSError> {
+        parse_links(&read_json(self.request("object/links", &[("arg", self.resolve_path(path)?)])?)?)
+    }
+
+    /// Lists the changes that turn the tree at `from` into the one at `to`.
+    pub fn object_diff(&self, from: &str, to: &str) -> Result<Vec<ObjectChange>, IPFSError> {
+        let args = [("arg", self.resolve_path(from)?), ("arg", self.resolve_path(to)?)];
+        parse_changes(&read_json(self.request("object/diff", &args)?)?)
+    }
+
+    /// Adds a link called `name` to `target` under the node at `root`,
+    /// creating intermediate nodes for a `/`-separated name if `create`.
+    /// Returns the CID of the new root.
+    pub fn object_patch_add_link(&self, root: &str, name: &str, target: &Cid, create: bool)
+                                 -> Result<Cid, IPFSError> {
+        let args = [("arg", self.resolve_path(root)?), ("arg", name.to_string()), ("arg", target.to_string()),
+                    ("create", create.to_string())];
+        hash_field(&read_json(self.request("object/patch/add-link", &args)?)?)
+    }
+
+    /// Removes the link called `name` from the node at `root`. Returns the
+    /// CID of the new root.
+    pub fn object_patch_rm_link(&self, root: &str, name: &str) -> Result<Cid, IPFSError> {
+        let args = [("arg", self.resolve_path(root)?), ("arg", name.to_string())];
+        hash_field(&read_json(self.request("object/patch/rm-link", &args)?)?)
+    }
+
+    /// Appends the contents of `data` to the node's data field.
+    pub fn object_patch_append_data<R: Read>(&self, root: &str, data: R) -> Result<Cid, IPFSError> {
+        self.patch_data("object/patch/append-data", root, data)
+    }
+
+    /// Replaces the node's data field with the contents of `data`.
+    pub fn object_patch_set_data<R: Read>(&self, root: &str, data: R) -> Result<Cid, IPFSError> {
+        self.patch_data("object/patch/set-data", root, data)
+    }
+
+    fn patch_data<R: Read>(&self, cmd: &str, root: &str, data: R) -> Result<Cid, IPFSError> {
+        let mut form = Multipart::new();
+        form.add_reader("data", data);
+        hash_field(&read_json(self.request_with_body(cmd, &[("arg", self.resolve_path(root)?)], &mut form)?)?)
+    }
+}
+
+/// Decodes the block `object/get` returned for `path`. When the path names
+/// the block directly, its bytes are checked against the CID first.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn decode_object(path: &str, block: &[u8]) -> Result<PBNode, IPFSError> {
+    if let Some(cid) = path_cid(path) {
+        cid.verify(block)?;
+    }
+    let mut object = PBNode::new();
+    object.merge_from_bytes(block)?;
+    Ok(object)
+}
+
+/// The CID a path such as `Qm...` or `/ipfs/Qm...` names directly, if it
+/// does not descend into a child.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn path_cid(path: &str) -> Option<Cid> {
+    let path = path.trim_start_matches("/ipfs/");
+    if path.contains('/') {
+        return None;
+    }
+    path.parse().ok()
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn hash_field(json: &Value) -> Result<Cid, IPFSError> {
+    string_field(json, "Hash")?.parse()
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_stat(json: &Value) -> Result<ObjectStat, IPFSError> {
+    Ok(ObjectStat {
+        cid: hash_field(json)?,
+        num_links: u64_field(json, "NumLinks")?,
+        block_size: u64_field(json, "BlockSize")?,
+        links_size: u64_field(json, "LinksSize")?,
+        data_size: u64_field(json, "DataSize")?,
+        cumulative_size: u64_field(json, "CumulativeSize")?
+    })
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_links(json: &Value) -> Result<Vec<ObjectLink>, IPFSError> {
+    // A node without links is reported with `"Links": null`.
+    let links = match json.get("Links").and_then(|links| links.as_array()) {
+        Some(links) => links,
+        None => return Ok(Vec::new())
+    };
+    links.iter().map(|link| {
+        Ok(ObjectLink { name: string_field(link, "Name")?, cid: hash_field(link)?, size: u64_field(link, "Size")? })
+    }).collect()
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_changes(json: &Value) -> Result<Vec<ObjectChange>, IPFSError> {
+    let changes = match json.get("Changes").and_then(|changes| changes.as_array()) {
+        Some(changes) => changes,
+        None => return Ok(Vec::new())
+    };
+    changes.iter().map(parse_change).collect()
+}
+
+/// Change types are numbered as in go-merkledag's `dagutils`. The CIDs are
+/// DAG-JSON links, or null.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn parse_change(json: &Value) -> Result<ObjectChange, IPFSError> {
+    let kind = match json.get("Type").and_then(|t| t.as_u64()) {
+        Some(0) => ChangeType::Add,
+        Some(1) => ChangeType::Remove,
+        Some(2) => ChangeType::Modify,
+        other => return Err(IPFSError::UnexpectedResponse(format!("unknown change type {:?}", other)))
+    };
+    let link = |key: &str| -> Result<Option<Cid>, IPFSError> {
+        match json.get(key).and_then(|link| link.get("/")).and_then(|cid| cid.as_str()) {
+            Some(cid) => Ok(Some(cid.parse()?)),
+            None => Ok(None)
+        }
+    };
+    Ok(ObjectChange { kind, path: string_field(json, "Path")?, before: link("Before")?, after: link("After")? })
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+
+    use super::*;
+    #[cfg(feature = "blocking")]
+    use cid::Codec;
+    #[cfg(feature = "blocking")]
+    use merkledag::PBLink;
+    #[cfg(feature = "blocking")]
+    use multihash::HashFunction;
+    use serde_json::{self, Value};
+    #[cfg(feature = "blocking")]
+    use tests::mock_daemon;
+
+    #[test]
+    fn changes_are_parsed() {
+        let json = serde_json::from_str::<Value>(r#"{"Type":2,"Path":"docs/index.html",
+                                      "Before":{"/":"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt"},
+                                      "After":{"/":"bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq"}}"#).unwrap();
+        let change = parse_change(&json).unwrap();
+        assert_eq!(change.kind, ChangeType::Modify);
+        assert_eq!(change.path, "docs/index.html");
+        assert!(change.before.is_some() && change.after.is_some());
+
+        let json = serde_json::from_str::<Value>(r#"{"Type":0,"Path":"new","Before":null,
+                                      "After":{"/":"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt"}}"#).unwrap();
+        assert_eq!(parse_change(&json).unwrap().before, None);
+    }
+
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn nodes_are_put_in_canonical_form() {
+        let mut node = PBNode::new();
+        for name in &["b", "a"] {
+            let mut link = PBLink::new();
+            link.set_Hash("QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt".parse::<Cid>().unwrap().to_bytes());
+            link.set_Name(name.to_string());
+            link.set_Tsize(23);
+            node.mut_Links().push(link);
+        }
+        let block = encode_dag_pb(&node).unwrap();
+        let cid = Cid::for_block(Codec::DagPb, HashFunction::Sha2_256, &block).unwrap().to_v0().unwrap();
+
+        let reply = format!(r#"{{"Hash":"{}","Links":[]}}"#, cid);
+        let (server, requests) = mock_daemon(move |_| reply.clone().into_bytes());
+        assert_eq!(server.object_put(&node).unwrap(), cid);
+        let sent = &requests.lock().unwrap()[0].1;
+        assert!(sent.windows(block.len()).any(|window| window == &block[..]));
+    }
+}
@@ -0,0 +1,225 @@
+use cid::Cid;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use response::read_json;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use response::string_field;
+#[cfg(feature = "blocking")]
+use response::JsonStream;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
+#[cfg(feature = "blocking")]
+use IPFS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinType {
+    /// Only the named block is pinned.
+    Direct,
+    /// The named block and everything below it are pinned.
+    Recursive,
+    /// The block is kept because a recursive pin above it covers it.
+    Indirect,
+    /// Every kind of pin; only meaningful as a `pin_ls` filter.
+    All
+}
+
+impl PinType {
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn as_str(&self) -> &'static str {
+        match *self {
+            PinType::Direct => "direct",
+            PinType::Recursive => "recursive",
+            PinType::Indirect => "indirect",
+            PinType::All => "all"
+        }
+    }
+
+    /// Parses the daemon's type string. Indirect pins are reported as
+    /// `indirect through <cid>`.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn parse(kind: &str) -> Result<PinType, IPFSError> {
+        match kind {
+            "direct" => Ok(PinType::Direct),
+            "recursive" => Ok(PinType::Recursive),
+            _ if kind.starts_with("indirect") => Ok(PinType::Indirect),
+            _ => Err(IPFSError::UnexpectedResponse(format!("unknown pin type {:?}", kind)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pin {
+    pub cid: Cid,
+    pub kind: PinType
+}
+
+/// A block that failed `pin_verify`, with the reason the daemon gave.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadNode {
+    pub cid: Cid,
+    pub error: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinVerification {
+    pub cid: Cid,
+    pub ok: bool,
+    pub bad_nodes: Vec<BadNode>
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Pins `path`, and everything below it if `recursive`. Returns the CIDs
+    /// that were pinned.
+    pub fn pin_add(&self, path: &str, recursive: bool) -> Result<Vec<Cid>, IPFSError> {
+        let res = self.request("pin/add", &[("arg", path.to_string()), ("recursive", recursive.to_string())])?;
+        pins_field(&read_json(res)?)
+    }
+
+    /// Removes the pin on `path`. Fails with `IPFSError::NotPinned` if there
+    /// is no pin of the given kind.
+    pub fn pin_rm(&self, path: &str, recursive: bool) -> Result<Vec<Cid>, IPFSError> {
+        let res = self.request("pin/rm", &[("arg", path.to_string()), ("recursive", recursive.to_string())])
+                      .map_err(|err| not_pinned(err, path))?;
+        pins_field(&read_json(res)?)
+    }
+
+    /// Lists pins of the given kind, or checks a single `path` if one is
+    /// given. The whole list is buffered; use `pin_ls_stream` for large sets.
+    pub fn pin_ls(&self, path: Option<&str>, filter: PinType) -> Result<Vec<Pin>, IPFSError> {
+        let mut args = vec![("type", filter.as_str().to_string())];
+        if let Some(path) = path {
+            args.push(("arg", path.to_string()));
+        }
+        let res = self.request("pin/ls", &args).map_err(|err| not_pinned(err, path.unwrap_or("")))?;
+        parse_pins(&read_json(res)?)
+    }
+
+    /// Lists pins of the given kind as the daemon finds them.
+    pub fn pin_ls_stream(&self, filter: PinType) -> Result<JsonStream<Pin>, IPFSError> {
+        let res = self.request("pin/ls", &[("type", filter.as_str().to_string()), ("stream", "true".to_string())])?;
+        Ok(JsonStream::new(res, parse_pin))
+    }
+
+    /// Moves a recursive pin from `from` to `to`, which is cheaper than
+    /// pinning the new tree and unpinning the old one when they share most
+    /// of their blocks. The old pin is kept if `unpin` is false.
+    pub fn pin_update(&self, from: &str, to: &str, unpin: bool) -> Result<Vec<Cid>, IPFSError> {
+        let res = self.request("pin/update", &[("arg", from.to_string()), ("arg", to.to_string()),
+                                               ("unpin", unpin.to_string())])
+                      .map_err(|err| not_pinned(err, from))?;
+        pins_field(&read_json(res)?)
+    }
+
+    /// Checks that every recursively pinned tree is complete and intact. With
+    /// `verbose` false the daemon only reports the pins that failed.
+    pub fn pin_verify(&self, verbose: bool) -> Result<JsonStream<PinVerification>, IPFSError> {
+        let res = self.request("pin/verify", &[("verbose", verbose.to_string())])?;
+        Ok(JsonStream::new(res, parse_verification))
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn pins_field(json: &Value) -> Result<Vec<Cid>, IPFSError> {
+    let pins = match json.get("Pins").and_then(|pins| pins.as_array()) {
+        Some(pins) => pins,
+        None => return Ok(Vec::new())
+    };
+    let mut cids = Vec::with_capacity(pins.len());
+    for pin in pins {
+        match pin.as_str() {
+            Some(cid) => cids.push(cid.parse()?),
+            None => return Err(IPFSError::UnexpectedResponse(format!("pin {} is not a string", pin)))
+        }
+    }
+    Ok(cids)
+}
+
+/// Reads the buffered `pin/ls` output, a `Keys` object mapping each CID to
+/// its pin type.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_pins(json: &Value) -> Result<Vec<Pin>, IPFSError> {
+    let keys = match json.get("Keys").and_then(|keys| keys.as_object()) {
+        Some(keys) => keys,
+        None => return Err(IPFSError::UnexpectedResponse("missing object field \"Keys\"".to_string()))
+    };
+    let mut pins = Vec::with_capacity(keys.len());
+    for (cid, info) in keys {
+        pins.push(Pin { cid: cid.parse()?, kind: PinType::parse(&string_field(info, "Type")?)? });
+    }
+    Ok(pins)
+}
+
+/// Reads one line of streamed `pin/ls` output.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_pin(json: &Value) -> Result<Pin, IPFSError> {
+    Ok(Pin { cid: string_field(json, "Cid")?.parse()?, kind: PinType::parse(&string_field(json, "Type")?)? })
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn parse_verification(json: &Value) -> Result<PinVerification, IPFSError> {
+    let status = json.get("PinStatus");
+    let mut bad_nodes = Vec::new();
+    if let Some(nodes) = status.and_then(|s| s.get("BadNodes")).and_then(|n| n.as_array()) {
+        for node in nodes {
+            bad_nodes.push(BadNode { cid: string_field(node, "Cid")?.parse()?, error: string_field(node, "Err")? });
+        }
+    }
+    Ok(PinVerification {
+        cid: string_field(json, "Cid")?.parse()?,
+        ok: status.and_then(|s| s.get("Ok")).and_then(|ok| ok.as_bool()).unwrap_or(false),
+        bad_nodes
+    })
+}
+
+/// Turns the daemon's "not pinned" errors into `IPFSError::NotPinned`.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub fn not_pinned(err: IPFSError, path: &str) -> IPFSError {
+    match err {
+        IPFSError::Api(ref api) if api.message.contains("not pinned") => IPFSError::NotPinned(path.to_string()),
+        other => other
+    }
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+
+    use super::*;
+    use error::ApiError;
+    use http::StatusCode;
+    use serde_json::{self, Value};
+
+    #[test]
+    fn pin_types_are_parsed() {
+        assert_eq!(PinType::parse("recursive").unwrap(), PinType::Recursive);
+        assert_eq!(PinType::parse("indirect through QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt").unwrap(),
+                   PinType::Indirect);
+        assert!(PinType::parse("sideways").is_err());
+    }
+
+    #[test]
+    fn verification_lists_bad_nodes() {
+        let json = serde_json::from_str::<Value>(r#"{"Cid":"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt",
+                                      "PinStatus":{"Ok":false,"BadNodes":[
+                                          {"Cid":"QmR6XorNYAywK4q1dRiRN1gmvfLcx3ccBv68iGtAqon9tt",
+                                           "Err":"merkledag: not found"}]}}"#).unwrap();
+        let verification = parse_verification(&json).unwrap();
+        assert!(!verification.ok);
+        assert_eq!(verification.bad_nodes[0].error, "merkledag: not found");
+    }
+
+    #[test]
+    fn not_pinned_errors_get_their_own_variant() {
+        let err = IPFSError::Api(ApiError {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: "not pinned or pinned indirectly".to_string(),
+            code: 0,
+            kind: "error".to_string()
+        });
+        match not_pinned(err, "/ipfs/Qm") {
+            IPFSError::NotPinned(path) => assert_eq!(path, "/ipfs/Qm"),
+            other => panic!("unexpected error {:?}", other)
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! Reassembles UnixFS files from their blocks without going through the
+//! daemon's `cat`, so chunked files can be read from any `BlockSource`.
+
+use attrs::{self, FileAttrs};
+use blocks::BlockSource;
+use cid::{Cid, Codec};
+use error::IPFSError;
+use merkledag::PBNode;
+use protobuf::Message;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use unixfs::{Data, Data_DataType};
+#[cfg(feature = "blocking")]
+use IPFS;
+
+/// A decoded file block: the bytes it holds itself, followed by its children
+/// in order along with the file size each child is expected to produce.
+#[derive(Clone)]
+pub struct FileNode {
+    pub data: Vec<u8>,
+    pub children: Vec<(Cid, u64)>,
+    pub filesize: u64,
+    /// Metadata recorded in the node; always empty for raw leaves.
+    pub attrs: FileAttrs
+}
+
+impl FileNode {
+    /// Decodes the block named by `cid`, which is either a raw leaf or a
+    /// dag-pb node carrying UnixFS `File` or `Raw` data.
+    pub fn decode(cid: &Cid, block: Vec<u8>) -> Result<FileNode, IPFSError> {
+        if cid.codec() == Codec::Raw {
+            let filesize = block.len() as u64;
+            return Ok(FileNode { data: block, children: Vec::new(), filesize, attrs: FileAttrs::default() });
+        }
+
+        let mut node = PBNode::new();
+        node.merge_from_bytes(&block)?;
+        let mut content = Data::new();
+        content.merge_from_bytes(node.get_Data())?;
+        match content.get_Type() {
+            Data_DataType::File | Data_DataType::Raw => {}
+            other => return Err(IPFSError::InvalidBlock(format!("expected a file node, found {:?}", other)))
+        }
+
+        let links = node.take_Links().into_vec();
+        let sizes = content.take_blocksizes();
+        if sizes.len() != links.len() {
+            return Err(IPFSError::InvalidBlock(format!("{} links but {} blocksizes", links.len(), sizes.len())));
+        }
+
+        let data = content.take_Data();
+        let filesize = match sizes.iter().try_fold(data.len() as u64, |total, &size| total.checked_add(size)) {
+            Some(filesize) => filesize,
+            None => return Err(IPFSError::InvalidBlock("blocksizes overflow a 64-bit filesize".to_string()))
+        };
+        if content.has_filesize() && content.get_filesize() != filesize {
+            return Err(IPFSError::InvalidBlock(format!("filesize {} does not match the {} bytes of its blocks",
+                                                       content.get_filesize(), filesize)));
+        }
+
+        let mut children = Vec::with_capacity(links.len());
+        for (link, size) in links.iter().zip(sizes) {
+            children.push((Cid::from_bytes(link.get_Hash())?, size));
+        }
+        Ok(FileNode { data, children, filesize, attrs: attrs::decode(&content)? })
+    }
+}
+
+/// The children of one node that are still to be read.
+struct Frame {
+    children: Vec<(Cid, u64)>,
+    next: usize
+}
+
+/// Streams the content of a UnixFS file, fetching blocks from `source` in
+/// order as the reader reaches them. Intermediate nodes of any depth and raw
+/// leaves are supported; every block's size is checked against the
+/// `blocksizes` its parent recorded.
+///
+/// Seeking uses those `blocksizes` to descend straight to the block holding
+/// the new position, so only the blocks on that path are fetched.
+pub struct FileReader<S: BlockSource> {
+    source: S,
+    root: FileNode,
+    stack: Vec<Frame>,
+    current: Cursor<Vec<u8>>,
+    pos: u64
+}
+
+impl<S: BlockSource> FileReader<S> {
+    /// Fetches the root block `cid` and prepares to read the file under it.
+    pub fn new(source: S, cid: &Cid) -> Result<FileReader<S>, IPFSError> {
+        let root = FileNode::decode(cid, source.get_block(cid)?)?;
+        Ok(FileReader {
+            source,
+            stack: vec![Frame { children: root.children.clone(), next: 0 }],
+            current: Cursor::new(root.data.clone()),
+            root,
+            pos: 0
+        })
+    }
+
+    /// The total size of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.root.filesize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.filesize == 0
+    }
+
+    /// The mode and modification time recorded in the file's root node.
+    pub fn attrs(&self) -> FileAttrs {
+        self.root.attrs
+    }
+
+    /// Writes the rest of the file to a new local file at `path` and restores
+    /// its recorded mode and modification time. Returns the bytes written.
+    pub fn extract_to(&mut self, path: &Path) -> Result<u64, IPFSError> {
+        let written = io::copy(self, &mut File::create(path)?)?;
+        self.root.attrs.apply(path)?;
+        Ok(written)
+    }
+
+    /// Fetches a child block and checks it against the size its parent
+    /// recorded for it.
+    fn load(&self, cid: &Cid, size: u64) -> Result<FileNode, IPFSError> {
+        let node = FileNode::decode(cid, self.source.get_block(cid)?)?;
+        if node.filesize != size {
+            return Err(IPFSError::InvalidBlock(format!("block holds {} bytes but its parent expects {}",
+                                                       node.filesize, size)));
+        }
+        Ok(node)
+    }
+
+    /// Loads the next block in file order into `current`. Returns false at
+    /// the end of the file.
+    fn advance(&mut self) -> Result<bool, IPFSError> {
+        loop {
+            let next = match self.stack.last_mut() {
+                None => return Ok(false),
+                Some(frame) if frame.next < frame.children.len() => {
+                    frame.next += 1;
+                    Some(frame.children[frame.next - 1].clone())
+                }
+                Some(_) => None
+            };
+            let (cid, size) = match next {
+                Some(child) => child,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            let node = self.load(&cid, size)?;
+            self.current = Cursor::new(node.data);
+            if !node.children.is_empty() {
+                self.stack.push(Frame { children: node.children, next: 0 });
+            }
+            return Ok(true);
+        }
+    }
+
+    /// Rebuilds the traversal state so that the next read starts at `offset`.
+    fn seek_to(&mut self, offset: u64) -> Result<(), IPFSError> {
+        self.stack.clear();
+        self.current = Cursor::new(Vec::new());
+        self.pos = offset;
+        if offset >= self.root.filesize {
+            return Ok(());
+        }
+
+        let mut node = self.root.clone();
+        let mut remaining = offset;
+        loop {
+            let data_len = node.data.len() as u64;
+            if remaining < data_len {
+                self.current = Cursor::new(node.data);
+                self.current.set_position(remaining);
+                self.stack.push(Frame { children: node.children, next: 0 });
+                return Ok(());
+            }
+            remaining -= data_len;
+
+            // Decoding checked that the sizes add up to the node's filesize,
+            // so the offset always falls inside one of the children.
+            let mut index = 0;
+            while remaining >= node.children[index].1 {
+                remaining -= node.children[index].1;
+                index += 1;
+            }
+            let (cid, size) = node.children[index].clone();
+            let child = self.load(&cid, size)?;
+            self.stack.push(Frame { children: node.children, next: index + 1 });
+            node = child;
+        }
+    }
+}
+
+impl<S: BlockSource> Read for FileReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.current.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                self.pos += n as u64;
+                return Ok(n);
+            }
+            match self.advance() {
+                Ok(true) => continue,
+                Ok(false) => return Ok(0),
+                Err(err) => return Err(io::Error::other(err))
+            }
+        }
+    }
+}
+
+impl<S: BlockSource> Seek for FileReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => offset_by(self.root.filesize, delta),
+            SeekFrom::Current(delta) => offset_by(self.pos, delta)
+        };
+        let target = match target {
+            Some(target) => target,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "seek to a negative position"))
+        };
+        match self.seek_to(target) {
+            Ok(()) => Ok(target),
+            Err(err) => Err(io::Error::other(err))
+        }
+    }
+}
+
+fn offset_by(base: u64, delta: i64) -> Option<u64> {
+    if delta >= 0 {
+        base.checked_add(delta as u64)
+    } else {
+        base.checked_sub(delta.unsigned_abs())
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl IPFS {
+    /// Opens the UnixFS file `cid` for random access, fetching its blocks
+    /// through the daemon's block API as they are needed.
+    pub fn open_file(&self, cid: &Cid) -> Result<FileReader<&IPFS>, IPFSError> {
+        FileReader::new(self, cid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use merkledag::{PBLink, PBNode};
+    use protobuf::Message;
+    use std::collections::HashMap;
+    use std::io::Read;
+    use unixfs::{Data, Data_DataType};
+
+    fn file_node(data: &[u8], links: Vec<(Cid, u64)>) -> Vec<u8> {
+        let mut content = Data::new();
+        content.set_Type(Data_DataType::File);
+        if !data.is_empty() {
+            content.set_Data(data.to_vec());
+        }
+        content.set_filesize(data.len() as u64 + links.iter().map(|l| l.1).sum::<u64>());
+        content.set_blocksizes(links.iter().map(|l| l.1).collect());
+
+        let mut node = PBNode::new();
+        node.set_Data(content.write_to_bytes().unwrap());
+        for (cid, _) in links {
+            let mut link = PBLink::new();
+            link.set_Hash(cid.to_bytes());
+            node.mut_Links().push(link);
+        }
+        node.write_to_bytes().unwrap()
+    }
+
+    fn multihash(n: u8) -> Vec<u8> {
+        let mut hash = vec![0x12, 0x20];
+        hash.extend_from_slice(&[n; 32]);
+        hash
+    }
+
+    fn v0(n: u8) -> Cid {
+        Cid::new_v0(multihash(n)).unwrap()
+    }
+
+    fn raw(n: u8) -> Cid {
+        Cid::new_v1(Codec::Raw, multihash(n)).unwrap()
+    }
+
+    #[test]
+    fn nested_file_with_raw_leaves_is_reassembled() {
+        let mut blocks = HashMap::new();
+        blocks.insert(raw(1), b"hello ".to_vec());
+        blocks.insert(raw(2), b"chunked ".to_vec());
+        blocks.insert(v0(3), file_node(b"world", vec![]));
+        blocks.insert(v0(4), file_node(b"", vec![(raw(1), 6), (raw(2), 8)]));
+        blocks.insert(v0(5), file_node(b"", vec![(v0(4), 14), (v0(3), 5)]));
+
+        let mut reader = FileReader::new(&blocks, &v0(5)).unwrap();
+        assert_eq!(reader.len(), 19);
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "hello chunked world");
+    }
+
+    #[test]
+    fn seek_fetches_only_the_blocks_on_the_path() {
+        use std::cell::RefCell;
+        use std::io::{Seek, SeekFrom};
+
+        struct Counting(HashMap<Cid, Vec<u8>>, RefCell<Vec<Cid>>);
+        impl BlockSource for Counting {
+            fn get_block(&self, cid: &Cid) -> Result<Vec<u8>, IPFSError> {
+                self.1.borrow_mut().push(cid.clone());
+                self.0.get_block(cid)
+            }
+        }
+
+        let mut blocks = HashMap::new();
+        blocks.insert(raw(1), b"0123".to_vec());
+        blocks.insert(raw(2), b"4567".to_vec());
+        blocks.insert(raw(3), b"89ab".to_vec());
+        blocks.insert(v0(4), file_node(b"", vec![(raw(1), 4), (raw(2), 4)]));
+        blocks.insert(v0(5), file_node(b"", vec![(v0(4), 8), (raw(3), 4)]));
+        let source = Counting(blocks, RefCell::new(Vec::new()));
+
+        let mut reader = FileReader::new(&source, &v0(5)).unwrap();
+        assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap(), 5);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"56");
+        assert_eq!(*source.1.borrow(), vec![v0(5), v0(4), raw(2)]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 9);
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "9ab");
+        assert_eq!(reader.seek(SeekFrom::Current(-6)).unwrap(), 6);
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "6789ab");
+    }
+
+    #[test]
+    fn mismatched_blocksizes_are_rejected() {
+        let mut blocks = HashMap::new();
+        blocks.insert(raw(1), b"short".to_vec());
+        blocks.insert(v0(2), file_node(b"", vec![(raw(1), 10)]));
+
+        let mut content = Vec::new();
+        assert!(FileReader::new(&blocks, &v0(2)).unwrap().read_to_end(&mut content).is_err());
+    }
+
+    #[test]
+    fn overflowing_blocksizes_are_rejected() {
+        let mut content = Data::new();
+        content.set_Type(Data_DataType::File);
+        content.set_blocksizes(vec![u64::MAX, 1]);
+        let mut node = PBNode::new();
+        node.set_Data(content.write_to_bytes().unwrap());
+        for n in 1..3 {
+            let mut link = PBLink::new();
+            link.set_Hash(raw(n).to_bytes());
+            node.mut_Links().push(link);
+        }
+        match FileNode::decode(&v0(1), node.write_to_bytes().unwrap()) {
+            Err(IPFSError::InvalidBlock(_)) => {}
+            other => panic!("unexpected result {:?}", other.map(|node| node.filesize))
+        }
+    }
+}
@@ -0,0 +1,348 @@
+#[cfg(feature = "blocking")]
+use cancel::CancelHandle;
+use error::IPFSError;
+#[cfg(feature = "blocking")]
+use reqwest::blocking::Response;
+use serde_json::{self, Value};
+use std::io::Read;
+#[cfg(feature = "blocking")]
+use std::io::{self, BufRead, BufReader, Cursor};
+#[cfg(feature = "blocking")]
+use std::sync::mpsc::{self, Receiver, SyncSender};
+#[cfg(feature = "blocking")]
+use std::thread;
+
+/// Reads a whole response body as a single JSON value.
+pub fn read_json<R: Read>(mut reader: R) -> Result<Value, IPFSError> {
+    Ok(serde_json::from_reader(&mut reader)?)
+}
+
+/// Looks up a string field in a JSON object.
+pub fn string_field(json: &Value, key: &str) -> Result<String, IPFSError> {
+    match json.get(key).and_then(|v| v.as_str()) {
+        Some(value) => Ok(value.to_string()),
+        None => Err(IPFSError::UnexpectedResponse(format!("missing string field {:?}", key)))
+    }
+}
+
+/// Looks up an unsigned integer field in a JSON object. The daemon encodes
+/// some sizes as strings (e.g. `"Size": "14"` in `add`), so both are accepted.
+pub fn u64_field(json: &Value, key: &str) -> Result<u64, IPFSError> {
+    let value = json.get(key);
+    if let Some(n) = value.and_then(|v| v.as_u64()) {
+        return Ok(n);
+    }
+    match value.and_then(|v| v.as_str()).and_then(|s| s.parse().ok()) {
+        Some(n) => Ok(n),
+        None => Err(IPFSError::UnexpectedResponse(format!("missing integer field {:?}", key)))
+    }
+}
+
+/// Iterates over a stream of newline-delimited JSON values, the format the
+/// daemon uses for commands that emit one object per result.
+#[cfg(feature = "blocking")]
+pub struct JsonLines<R> {
+    reader: BufReader<R>
+}
+
+#[cfg(feature = "blocking")]
+impl<R: Read> JsonLines<R> {
+    pub fn new(reader: R) -> JsonLines<R> {
+        JsonLines { reader: BufReader::new(reader) }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<R: Read> Iterator for JsonLines<R> {
+    type Item = Result<Value, IPFSError>;
+
+    fn next(&mut self) -> Option<Result<Value, IPFSError>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) if line.trim().is_empty() => continue,
+                Ok(_) => return Some(serde_json::from_str::<Value>(&line).map_err(IPFSError::from)),
+                Err(err) => return Some(Err(IPFSError::from(err)))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A streaming response body, such as the bytes of a file from `cat`.
+/// Content is read from the daemon as it is consumed rather than buffered:
+/// a thread of its own reads a few chunks ahead, so that cancelling can
+/// interrupt a read that is waiting on the daemon.
+///
+/// Once the reader is cancelled or dropped, that thread closes the response
+/// as soon as the read it has in progress returns. A read is bounded by the
+/// client's `timeout`, so without one a daemon that stalls for good keeps
+/// the thread and its connection until it sends more or hangs up.
+#[cfg(feature = "blocking")]
+pub struct ResponseReader {
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    current: Cursor<Vec<u8>>,
+    finished: bool,
+    error: Option<io::Error>,
+    cancel: CancelHandle
+}
+
+#[cfg(feature = "blocking")]
+impl ResponseReader {
+    pub fn new(res: Response) -> ResponseReader {
+        let (sender, receiver) = mpsc::sync_channel(4);
+        let cancel = CancelHandle::new();
+        cancel.interrupt_with(sender.clone());
+        let cancelled = cancel.clone();
+        thread::spawn(move || read_chunks(res, sender, cancelled));
+        ResponseReader { chunks: receiver, current: Cursor::new(Vec::new()), finished: false, error: None, cancel }
+    }
+
+    /// A handle that makes reads fail, including one already waiting on the
+    /// daemon, e.g. to abandon a large download from another thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+}
+
+/// Reads `res` into `sender` until the body ends, which is marked by an
+/// empty chunk, or fails. Stops early, dropping the response, if the reader
+/// is cancelled or dropped, though only once the read in progress returns.
+#[cfg(feature = "blocking")]
+fn read_chunks(mut res: Response, sender: SyncSender<io::Result<Vec<u8>>>, cancel: CancelHandle) {
+    let mut buf = vec![0; CHUNK_SIZE];
+    while !cancel.is_cancelled() {
+        let chunk = res.read(&mut buf).map(|n| buf[..n].to_vec());
+        let last = chunk.as_ref().map_or(true, |chunk| chunk.is_empty());
+        if cancel.is_cancelled() || sender.send(chunk).is_err() || last {
+            return;
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Read for ResponseReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.cancel.is_cancelled() {
+                // Frees the thread reading ahead if it is waiting for room,
+                // so that it sees the cancel and stops.
+                while self.chunks.try_recv().is_ok() {}
+                return Err(io::Error::other(IPFSError::Cancelled.to_string()));
+            }
+            if let Some(ref err) = self.error {
+                return Err(io::Error::new(err.kind(), err.to_string()));
+            }
+            let n = self.current.read(buf)?;
+            if n > 0 || buf.is_empty() || self.finished {
+                return Ok(n);
+            }
+            // The cancel handle holds a sender too, so the end of the body
+            // is marked rather than left to the channel closing.
+            match self.chunks.recv() {
+                Ok(Ok(chunk)) => {
+                    self.finished = chunk.is_empty();
+                    self.current = Cursor::new(chunk);
+                }
+                Ok(Err(err)) => self.error = Some(err),
+                Err(_) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "response body ended early"))
+            }
+        }
+    }
+}
+
+/// The newline-delimited JSON values of a response, which end with
+/// `IPFSError::Cancelled` once the stream is cancelled, whatever the read
+/// in progress made of it.
+#[cfg(feature = "blocking")]
+pub struct ResponseLines {
+    lines: JsonLines<ResponseReader>,
+    cancel: CancelHandle,
+    finished: bool
+}
+
+#[cfg(feature = "blocking")]
+impl ResponseLines {
+    pub fn new(res: Response) -> ResponseLines {
+        let reader = ResponseReader::new(res);
+        let cancel = reader.cancel_handle();
+        ResponseLines { lines: JsonLines::new(reader), cancel, finished: false }
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Iterator for ResponseLines {
+    type Item = Result<Value, IPFSError>;
+
+    fn next(&mut self) -> Option<Result<Value, IPFSError>> {
+        if self.finished {
+            return None;
+        }
+        if !self.cancel.is_cancelled() {
+            match self.lines.next() {
+                Some(Err(_)) if self.cancel.is_cancelled() => {}
+                next => return next
+            }
+        }
+        self.finished = true;
+        Some(Err(IPFSError::Cancelled))
+    }
+}
+
+/// A typed stream of results from a command that emits newline-delimited
+/// JSON, decoding each object as it arrives.
+#[cfg(feature = "blocking")]
+pub struct JsonStream<T> {
+    lines: ResponseLines,
+    parse: fn(&Value) -> Result<T, IPFSError>
+}
+
+#[cfg(feature = "blocking")]
+impl<T> JsonStream<T> {
+    pub fn new(res: Response, parse: fn(&Value) -> Result<T, IPFSError>) -> JsonStream<T> {
+        JsonStream { lines: ResponseLines::new(res), parse }
+    }
+
+    /// A handle that ends the stream from another thread, e.g. to stop a
+    /// `pin/verify` of a large repo.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.lines.cancel_handle()
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<T> Iterator for JsonStream<T> {
+    type Item = Result<T, IPFSError>;
+
+    fn next(&mut self) -> Option<Result<T, IPFSError>> {
+        self.lines.next().map(|line| line.and_then(|json| (self.parse)(&json)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    #[cfg(feature = "blocking")]
+    use http;
+    #[cfg(feature = "blocking")]
+    use reqwest;
+    #[cfg(feature = "blocking")]
+    use std::io::Write;
+    #[cfg(feature = "blocking")]
+    use std::net::TcpListener;
+    #[cfg(feature = "blocking")]
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn deeply_nested_json_is_an_error() {
+        let body = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        match read_json(body.as_bytes()) {
+            Err(IPFSError::Json(_)) => {}
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn cancelled_streams_end_with_an_error() {
+        let res = http::Response::builder().body("{\"Cid\":\"a\"}\n{\"Cid\":\"b\"}\n").unwrap();
+        let mut lines = ResponseLines::new(Response::from(res));
+        assert!(lines.next().unwrap().is_ok());
+        lines.cancel_handle().cancel();
+        match lines.next() {
+            Some(Err(IPFSError::Cancelled)) => {}
+            other => panic!("unexpected item {:?}", other)
+        }
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn cancelling_interrupts_a_stalled_read() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (done, stalled) = mpsc::channel::<()>();
+        let daemon = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::from("request line");
+            while line != "\r\n" {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+            }
+            reader.get_mut().write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nfirst").unwrap();
+            let _ = stalled.recv();
+        });
+
+        let res = reqwest::blocking::get(format!("http://127.0.0.1:{}/", port).as_str()).unwrap();
+        let mut reader = ResponseReader::new(res);
+        let mut buf = [0; 16];
+        let n = reader.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"first");
+
+        let cancel = reader.cancel_handle();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            cancel.cancel();
+        });
+        let start = Instant::now();
+        let err = reader.read(&mut buf).unwrap_err();
+        assert_eq!(err.to_string(), IPFSError::Cancelled.to_string());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        canceller.join().unwrap();
+        done.send(()).unwrap();
+        daemon.join().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn cancelling_stops_reading_ahead() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let daemon = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::from("request line");
+            while line != "\r\n" {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+            }
+            let stream = reader.get_mut();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100000\r\n\r\nfirst").unwrap();
+            // Trickles the body until the client hangs up, which it only
+            // does once the thread reading ahead stops.
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_secs(10) {
+                if stream.write_all(b".").is_err() {
+                    return Some(Instant::now());
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            None
+        });
+
+        let res = reqwest::blocking::get(format!("http://127.0.0.1:{}/", port).as_str()).unwrap();
+        let mut reader = ResponseReader::new(res);
+        let mut buf = [0; 5];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"first");
+        reader.cancel_handle().cancel();
+        let cancelled = Instant::now();
+        assert!(reader.read(&mut buf).is_err());
+
+        // The reader is still held, so only the cancel can have stopped it.
+        let hung_up = daemon.join().unwrap().expect("the response was never closed");
+        assert!(hung_up.duration_since(cancelled) < Duration::from_secs(5));
+        drop(reader);
+    }
+}
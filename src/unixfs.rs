@@ -1,25 +1,48 @@
-// This file is generated. Do not edit
+// This file is generated by rust-protobuf 2.28.0. Do not edit
 // @generated
 
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
 #![allow(dead_code)]
+#![allow(missing_docs)]
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
 #![allow(unused_imports)]
+#![allow(unused_results)]
+//! Generated file from `unixfs.proto`
 
-use protobuf::Message as Message_imported_for_functions;
-use protobuf::ProtobufEnum as ProtobufEnum_imported_for_functions;
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+// const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_2_28_0;
 
-#[derive(Clone,Default)]
+#[derive(PartialEq,Clone,Default)]
 pub struct Data {
     // message fields
     Type: ::std::option::Option<Data_DataType>,
     Data: ::protobuf::SingularField<::std::vec::Vec<u8>>,
     filesize: ::std::option::Option<u64>,
-    blocksizes: ::std::vec::Vec<u64>,
+    pub blocksizes: ::std::vec::Vec<u64>,
+    hashType: ::std::option::Option<u64>,
+    fanout: ::std::option::Option<u64>,
+    mode: ::std::option::Option<u32>,
+    pub mtime: ::protobuf::SingularPtrField<UnixTime>,
     // special fields
-    unknown_fields: ::protobuf::UnknownFields,
-    cached_size: ::std::cell::Cell<u32>,
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Data {
+    fn default() -> &'a Data {
+        <Data as ::protobuf::Message>::default_instance()
+    }
 }
 
 impl Data {
@@ -27,27 +50,12 @@ impl Data {
         ::std::default::Default::default()
     }
 
-    pub fn default_instance() -> &'static Data {
-        static mut instance: ::protobuf::lazy::Lazy<Data> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const Data,
-        };
-        unsafe {
-            instance.get(|| {
-                Data {
-                    Type: ::std::option::Option::None,
-                    Data: ::protobuf::SingularField::none(),
-                    filesize: ::std::option::Option::None,
-                    blocksizes: ::std::vec::Vec::new(),
-                    unknown_fields: ::protobuf::UnknownFields::new(),
-                    cached_size: ::std::cell::Cell::new(0),
-                }
-            })
-        }
-    }
-
     // required .unixfs.pb.Data.DataType Type = 1;
 
+
+    pub fn get_Type(&self) -> Data_DataType {
+        self.Type.unwrap_or(Data_DataType::Raw)
+    }
     pub fn clear_Type(&mut self) {
         self.Type = ::std::option::Option::None;
     }
@@ -61,12 +69,15 @@ impl Data {
         self.Type = ::std::option::Option::Some(v);
     }
 
-    pub fn get_Type<'a>(&self) -> Data_DataType {
-        self.Type.unwrap_or(Data_DataType::Raw)
-    }
-
     // optional bytes Data = 2;
 
+
+    pub fn get_Data(&self) -> &[u8] {
+        match self.Data.as_ref() {
+            Some(v) => &v,
+            None => &[],
+        }
+    }
     pub fn clear_Data(&mut self) {
         self.Data.clear();
     }
@@ -82,10 +93,10 @@ impl Data {
 
     // Mutable pointer to the field.
     // If field is not initialized, it is initialized with default value first.
-    pub fn mut_Data<'a>(&'a mut self) -> &'a mut ::std::vec::Vec<u8> {
+    pub fn mut_Data(&mut self) -> &mut ::std::vec::Vec<u8> {
         if self.Data.is_none() {
             self.Data.set_default();
-        };
+        }
         self.Data.as_mut().unwrap()
     }
 
@@ -94,15 +105,12 @@ impl Data {
         self.Data.take().unwrap_or_else(|| ::std::vec::Vec::new())
     }
 
-    pub fn get_Data<'a>(&'a self) -> &'a [u8] {
-        match self.Data.as_ref() {
-            Some(v) => &v,
-            None => &[],
-        }
-    }
-
     // optional uint64 filesize = 3;
 
+
+    pub fn get_filesize(&self) -> u64 {
+        self.filesize.unwrap_or(0)
+    }
     pub fn clear_filesize(&mut self) {
         self.filesize = ::std::option::Option::None;
     }
@@ -116,12 +124,12 @@ impl Data {
         self.filesize = ::std::option::Option::Some(v);
     }
 
-    pub fn get_filesize<'a>(&self) -> u64 {
-        self.filesize.unwrap_or(0)
-    }
-
     // repeated uint64 blocksizes = 4;
 
+
+    pub fn get_blocksizes(&self) -> &[u64] {
+        &self.blocksizes
+    }
     pub fn clear_blocksizes(&mut self) {
         self.blocksizes.clear();
     }
@@ -132,7 +140,7 @@ impl Data {
     }
 
     // Mutable pointer to the field.
-    pub fn mut_blocksizes<'a>(&'a mut self) -> &'a mut ::std::vec::Vec<u64> {
+    pub fn mut_blocksizes(&mut self) -> &mut ::std::vec::Vec<u64> {
         &mut self.blocksizes
     }
 
@@ -141,8 +149,94 @@ impl Data {
         ::std::mem::replace(&mut self.blocksizes, ::std::vec::Vec::new())
     }
 
-    pub fn get_blocksizes<'a>(&'a self) -> &'a [u64] {
-        &self.blocksizes
+    // optional uint64 hashType = 5;
+
+
+    pub fn get_hashType(&self) -> u64 {
+        self.hashType.unwrap_or(0)
+    }
+    pub fn clear_hashType(&mut self) {
+        self.hashType = ::std::option::Option::None;
+    }
+
+    pub fn has_hashType(&self) -> bool {
+        self.hashType.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_hashType(&mut self, v: u64) {
+        self.hashType = ::std::option::Option::Some(v);
+    }
+
+    // optional uint64 fanout = 6;
+
+
+    pub fn get_fanout(&self) -> u64 {
+        self.fanout.unwrap_or(0)
+    }
+    pub fn clear_fanout(&mut self) {
+        self.fanout = ::std::option::Option::None;
+    }
+
+    pub fn has_fanout(&self) -> bool {
+        self.fanout.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_fanout(&mut self, v: u64) {
+        self.fanout = ::std::option::Option::Some(v);
+    }
+
+    // optional uint32 mode = 7;
+
+
+    pub fn get_mode(&self) -> u32 {
+        self.mode.unwrap_or(0)
+    }
+    pub fn clear_mode(&mut self) {
+        self.mode = ::std::option::Option::None;
+    }
+
+    pub fn has_mode(&self) -> bool {
+        self.mode.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_mode(&mut self, v: u32) {
+        self.mode = ::std::option::Option::Some(v);
+    }
+
+    // optional .unixfs.pb.UnixTime mtime = 8;
+
+
+    pub fn get_mtime(&self) -> &UnixTime {
+        self.mtime.as_ref().unwrap_or_else(|| <UnixTime as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_mtime(&mut self) {
+        self.mtime.clear();
+    }
+
+    pub fn has_mtime(&self) -> bool {
+        self.mtime.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_mtime(&mut self, v: UnixTime) {
+        self.mtime = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_mtime(&mut self) -> &mut UnixTime {
+        if self.mtime.is_none() {
+            self.mtime.set_default();
+        }
+        self.mtime.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_mtime(&mut self) -> UnixTime {
+        self.mtime.take().unwrap_or_else(|| UnixTime::new())
     }
 }
 
@@ -150,41 +244,61 @@ impl ::protobuf::Message for Data {
     fn is_initialized(&self) -> bool {
         if self.Type.is_none() {
             return false;
+        }
+        for v in &self.mtime {
+            if !v.is_initialized() {
+                return false;
+            }
         };
         true
     }
 
-    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
-        while !try!(is.eof()) {
-            let (field_number, wire_type) = try!(is.read_tag_unpack());
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
                 1 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
-                        return ::std::result::Result::Err(::protobuf::ProtobufError::WireError("unexpected wire type".to_string()));
-                    };
-                    let tmp = try!(is.read_enum());
-                    self.Type = ::std::option::Option::Some(tmp);
+                    ::protobuf::rt::read_proto2_enum_with_unknown_fields_into(wire_type, is, &mut self.Type, 1, &mut self.unknown_fields)?
                 },
                 2 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
-                        return ::std::result::Result::Err(::protobuf::ProtobufError::WireError("unexpected wire type".to_string()));
-                    };
-                    let tmp = self.Data.set_default();
-                    try!(is.read_bytes_into(tmp))
+                    ::protobuf::rt::read_singular_bytes_into(wire_type, is, &mut self.Data)?;
                 },
                 3 => {
                     if wire_type != ::protobuf::wire_format::WireTypeVarint {
-                        return ::std::result::Result::Err(::protobuf::ProtobufError::WireError("unexpected wire type".to_string()));
-                    };
-                    let tmp = try!(is.read_uint64());
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
                     self.filesize = ::std::option::Option::Some(tmp);
                 },
                 4 => {
-                    try!(::protobuf::rt::read_repeated_uint64_into(wire_type, is, &mut self.blocksizes));
+                    ::protobuf::rt::read_repeated_uint64_into(wire_type, is, &mut self.blocksizes)?;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.hashType = ::std::option::Option::Some(tmp);
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.fanout = ::std::option::Option::Some(tmp);
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.mode = ::std::option::Option::Some(tmp);
+                },
+                8 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.mtime)?;
                 },
                 _ => {
-                    let unknown = try!(is.read_unknown(wire_type));
-                    self.mut_unknown_fields().add_value(field_number, unknown);
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
             };
         }
@@ -195,37 +309,64 @@ impl ::protobuf::Message for Data {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        for value in self.Type.iter() {
-            my_size += ::protobuf::rt::enum_size(1, *value);
-        };
-        for value in self.Data.iter() {
-            my_size += ::protobuf::rt::bytes_size(2, &value);
-        };
-        for value in self.filesize.iter() {
-            my_size += ::protobuf::rt::value_size(3, *value, ::protobuf::wire_format::WireTypeVarint);
-        };
-        for value in self.blocksizes.iter() {
+        if let Some(v) = self.Type {
+            my_size += ::protobuf::rt::enum_size(1, v);
+        }
+        if let Some(ref v) = self.Data.as_ref() {
+            my_size += ::protobuf::rt::bytes_size(2, &v);
+        }
+        if let Some(v) = self.filesize {
+            my_size += ::protobuf::rt::value_size(3, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.blocksizes {
             my_size += ::protobuf::rt::value_size(4, *value, ::protobuf::wire_format::WireTypeVarint);
         };
+        if let Some(v) = self.hashType {
+            my_size += ::protobuf::rt::value_size(5, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.fanout {
+            my_size += ::protobuf::rt::value_size(6, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.mode {
+            my_size += ::protobuf::rt::value_size(7, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(ref v) = self.mtime.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
-    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         if let Some(v) = self.Type {
-            try!(os.write_enum(1, v as i32));
-        };
-        if let Some(v) = self.Data.as_ref() {
-            try!(os.write_bytes(2, &v));
-        };
+            os.write_enum(1, ::protobuf::ProtobufEnum::value(&v))?;
+        }
+        if let Some(ref v) = self.Data.as_ref() {
+            os.write_bytes(2, &v)?;
+        }
         if let Some(v) = self.filesize {
-            try!(os.write_uint64(3, v));
-        };
-        for v in self.blocksizes.iter() {
-            try!(os.write_uint64(4, *v));
+            os.write_uint64(3, v)?;
+        }
+        for v in &self.blocksizes {
+            os.write_uint64(4, *v)?;
         };
-        try!(os.write_unknown_fields(self.get_unknown_fields()));
+        if let Some(v) = self.hashType {
+            os.write_uint64(5, v)?;
+        }
+        if let Some(v) = self.fanout {
+            os.write_uint64(6, v)?;
+        }
+        if let Some(v) = self.mode {
+            os.write_uint32(7, v)?;
+        }
+        if let Some(ref v) = self.mtime.as_ref() {
+            os.write_tag(8, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
 
@@ -233,92 +374,113 @@ impl ::protobuf::Message for Data {
         self.cached_size.get()
     }
 
-    fn get_unknown_fields<'s>(&'s self) -> &'s ::protobuf::UnknownFields {
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
         &self.unknown_fields
     }
 
-    fn mut_unknown_fields<'s>(&'s mut self) -> &'s mut ::protobuf::UnknownFields {
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
         &mut self.unknown_fields
     }
 
-    fn type_id(&self) -> ::std::any::TypeId {
-        ::std::any::TypeId::of::<Data>()
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
     }
-
-    fn as_any(&self) -> &::std::any::Any {
-        self as &::std::any::Any
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
     }
 
     fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
-        ::protobuf::MessageStatic::descriptor_static(None::<Self>)
+        Self::descriptor_static()
     }
-}
 
-impl ::protobuf::MessageStatic for Data {
     fn new() -> Data {
         Data::new()
     }
 
-    fn descriptor_static(_: ::std::option::Option<Data>) -> &'static ::protobuf::reflect::MessageDescriptor {
-        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
-        };
-        unsafe {
-            descriptor.get(|| {
-                let mut fields = ::std::vec::Vec::new();
-                fields.push(::protobuf::reflect::accessor::make_singular_enum_accessor(
-                    "Type",
-                    Data::has_Type,
-                    Data::get_Type,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_bytes_accessor(
-                    "Data",
-                    Data::has_Data,
-                    Data::get_Data,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
-                    "filesize",
-                    Data::has_filesize,
-                    Data::get_filesize,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_repeated_u64_accessor(
-                    "blocksizes",
-                    Data::get_blocksizes,
-                ));
-                ::protobuf::reflect::MessageDescriptor::new::<Data>(
-                    "Data",
-                    fields,
-                    file_descriptor_proto()
-                )
-            })
-        }
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeEnum<Data_DataType>>(
+                "Type",
+                |m: &Data| { &m.Type },
+                |m: &mut Data| { &mut m.Type },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "Data",
+                |m: &Data| { &m.Data },
+                |m: &mut Data| { &mut m.Data },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "filesize",
+                |m: &Data| { &m.filesize },
+                |m: &mut Data| { &mut m.filesize },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "blocksizes",
+                |m: &Data| { &m.blocksizes },
+                |m: &mut Data| { &mut m.blocksizes },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "hashType",
+                |m: &Data| { &m.hashType },
+                |m: &mut Data| { &mut m.hashType },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "fanout",
+                |m: &Data| { &m.fanout },
+                |m: &mut Data| { &mut m.fanout },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "mode",
+                |m: &Data| { &m.mode },
+                |m: &mut Data| { &mut m.mode },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<UnixTime>>(
+                "mtime",
+                |m: &Data| { &m.mtime },
+                |m: &mut Data| { &mut m.mtime },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Data>(
+                "Data",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Data {
+        static instance: ::protobuf::rt::LazyV2<Data> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Data::new)
     }
 }
 
 impl ::protobuf::Clear for Data {
     fn clear(&mut self) {
-        self.clear_Type();
-        self.clear_Data();
-        self.clear_filesize();
-        self.clear_blocksizes();
+        self.Type = ::std::option::Option::None;
+        self.Data.clear();
+        self.filesize = ::std::option::Option::None;
+        self.blocksizes.clear();
+        self.hashType = ::std::option::Option::None;
+        self.fanout = ::std::option::Option::None;
+        self.mode = ::std::option::Option::None;
+        self.mtime.clear();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::cmp::PartialEq for Data {
-    fn eq(&self, other: &Data) -> bool {
-        self.Type == other.Type &&
-        self.Data == other.Data &&
-        self.filesize == other.filesize &&
-        self.blocksizes == other.blocksizes &&
-        self.unknown_fields == other.unknown_fields
+impl ::std::fmt::Debug for Data {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::std::fmt::Debug for Data {
-    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-        ::protobuf::text_format::fmt(self, f)
+impl ::protobuf::reflect::ProtobufValue for Data {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
@@ -328,6 +490,8 @@ pub enum Data_DataType {
     Directory = 1,
     File = 2,
     Metadata = 3,
+    Symlink = 4,
+    HAMTShard = 5,
 }
 
 impl ::protobuf::ProtobufEnum for Data_DataType {
@@ -341,33 +505,60 @@ impl ::protobuf::ProtobufEnum for Data_DataType {
             1 => ::std::option::Option::Some(Data_DataType::Directory),
             2 => ::std::option::Option::Some(Data_DataType::File),
             3 => ::std::option::Option::Some(Data_DataType::Metadata),
+            4 => ::std::option::Option::Some(Data_DataType::Symlink),
+            5 => ::std::option::Option::Some(Data_DataType::HAMTShard),
             _ => ::std::option::Option::None
         }
     }
 
-    fn enum_descriptor_static(_: Option<Data_DataType>) -> &'static ::protobuf::reflect::EnumDescriptor {
-        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const ::protobuf::reflect::EnumDescriptor,
-        };
-        unsafe {
-            descriptor.get(|| {
-                ::protobuf::reflect::EnumDescriptor::new("Data_DataType", file_descriptor_proto())
-            })
-        }
+    fn values() -> &'static [Self] {
+        static values: &'static [Data_DataType] = &[
+            Data_DataType::Raw,
+            Data_DataType::Directory,
+            Data_DataType::File,
+            Data_DataType::Metadata,
+            Data_DataType::Symlink,
+            Data_DataType::HAMTShard,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<Data_DataType>("Data.DataType", file_descriptor_proto())
+        })
     }
 }
 
 impl ::std::marker::Copy for Data_DataType {
 }
 
-#[derive(Clone,Default)]
+impl ::std::default::Default for Data_DataType {
+    fn default() -> Self {
+        Data_DataType::Raw
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Data_DataType {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
 pub struct Metadata {
     // message fields
     MimeType: ::protobuf::SingularField<::std::string::String>,
     // special fields
-    unknown_fields: ::protobuf::UnknownFields,
-    cached_size: ::std::cell::Cell<u32>,
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Metadata {
+    fn default() -> &'a Metadata {
+        <Metadata as ::protobuf::Message>::default_instance()
+    }
 }
 
 impl Metadata {
@@ -375,24 +566,15 @@ impl Metadata {
         ::std::default::Default::default()
     }
 
-    pub fn default_instance() -> &'static Metadata {
-        static mut instance: ::protobuf::lazy::Lazy<Metadata> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const Metadata,
-        };
-        unsafe {
-            instance.get(|| {
-                Metadata {
-                    MimeType: ::protobuf::SingularField::none(),
-                    unknown_fields: ::protobuf::UnknownFields::new(),
-                    cached_size: ::std::cell::Cell::new(0),
-                }
-            })
-        }
-    }
-
     // required string MimeType = 1;
 
+
+    pub fn get_MimeType(&self) -> &str {
+        match self.MimeType.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
     pub fn clear_MimeType(&mut self) {
         self.MimeType.clear();
     }
@@ -408,10 +590,10 @@ impl Metadata {
 
     // Mutable pointer to the field.
     // If field is not initialized, it is initialized with default value first.
-    pub fn mut_MimeType<'a>(&'a mut self) -> &'a mut ::std::string::String {
+    pub fn mut_MimeType(&mut self) -> &mut ::std::string::String {
         if self.MimeType.is_none() {
             self.MimeType.set_default();
-        };
+        }
         self.MimeType.as_mut().unwrap()
     }
 
@@ -419,37 +601,25 @@ impl Metadata {
     pub fn take_MimeType(&mut self) -> ::std::string::String {
         self.MimeType.take().unwrap_or_else(|| ::std::string::String::new())
     }
-
-    pub fn get_MimeType<'a>(&'a self) -> &'a str {
-        match self.MimeType.as_ref() {
-            Some(v) => &v,
-            None => "",
-        }
-    }
 }
 
 impl ::protobuf::Message for Metadata {
     fn is_initialized(&self) -> bool {
         if self.MimeType.is_none() {
             return false;
-        };
+        }
         true
     }
 
-    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
-        while !try!(is.eof()) {
-            let (field_number, wire_type) = try!(is.read_tag_unpack());
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
                 1 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
-                        return ::std::result::Result::Err(::protobuf::ProtobufError::WireError("unexpected wire type".to_string()));
-                    };
-                    let tmp = self.MimeType.set_default();
-                    try!(is.read_string_into(tmp))
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.MimeType)?;
                 },
                 _ => {
-                    let unknown = try!(is.read_unknown(wire_type));
-                    self.mut_unknown_fields().add_value(field_number, unknown);
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
             };
         }
@@ -460,19 +630,19 @@ impl ::protobuf::Message for Metadata {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        for value in self.MimeType.iter() {
-            my_size += ::protobuf::rt::string_size(1, &value);
-        };
+        if let Some(ref v) = self.MimeType.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
-    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
-        if let Some(v) = self.MimeType.as_ref() {
-            try!(os.write_string(1, &v));
-        };
-        try!(os.write_unknown_fields(self.get_unknown_fields()));
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.MimeType.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
 
@@ -480,144 +650,297 @@ impl ::protobuf::Message for Metadata {
         self.cached_size.get()
     }
 
-    fn get_unknown_fields<'s>(&'s self) -> &'s ::protobuf::UnknownFields {
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
         &self.unknown_fields
     }
 
-    fn mut_unknown_fields<'s>(&'s mut self) -> &'s mut ::protobuf::UnknownFields {
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
         &mut self.unknown_fields
     }
 
-    fn type_id(&self) -> ::std::any::TypeId {
-        ::std::any::TypeId::of::<Metadata>()
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
     }
-
-    fn as_any(&self) -> &::std::any::Any {
-        self as &::std::any::Any
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
     }
 
     fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
-        ::protobuf::MessageStatic::descriptor_static(None::<Self>)
+        Self::descriptor_static()
     }
-}
 
-impl ::protobuf::MessageStatic for Metadata {
     fn new() -> Metadata {
         Metadata::new()
     }
 
-    fn descriptor_static(_: ::std::option::Option<Metadata>) -> &'static ::protobuf::reflect::MessageDescriptor {
-        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
-        };
-        unsafe {
-            descriptor.get(|| {
-                let mut fields = ::std::vec::Vec::new();
-                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
-                    "MimeType",
-                    Metadata::has_MimeType,
-                    Metadata::get_MimeType,
-                ));
-                ::protobuf::reflect::MessageDescriptor::new::<Metadata>(
-                    "Metadata",
-                    fields,
-                    file_descriptor_proto()
-                )
-            })
-        }
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "MimeType",
+                |m: &Metadata| { &m.MimeType },
+                |m: &mut Metadata| { &mut m.MimeType },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Metadata>(
+                "Metadata",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Metadata {
+        static instance: ::protobuf::rt::LazyV2<Metadata> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Metadata::new)
     }
 }
 
 impl ::protobuf::Clear for Metadata {
     fn clear(&mut self) {
-        self.clear_MimeType();
+        self.MimeType.clear();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::cmp::PartialEq for Metadata {
-    fn eq(&self, other: &Metadata) -> bool {
-        self.MimeType == other.MimeType &&
-        self.unknown_fields == other.unknown_fields
+impl ::std::fmt::Debug for Metadata {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::std::fmt::Debug for Metadata {
-    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+impl ::protobuf::reflect::ProtobufValue for Metadata {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct UnixTime {
+    // message fields
+    Seconds: ::std::option::Option<i64>,
+    FractionalNanoseconds: ::std::option::Option<u32>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a UnixTime {
+    fn default() -> &'a UnixTime {
+        <UnixTime as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl UnixTime {
+    pub fn new() -> UnixTime {
+        ::std::default::Default::default()
+    }
+
+    // required int64 Seconds = 1;
+
+
+    pub fn get_Seconds(&self) -> i64 {
+        self.Seconds.unwrap_or(0)
+    }
+    pub fn clear_Seconds(&mut self) {
+        self.Seconds = ::std::option::Option::None;
+    }
+
+    pub fn has_Seconds(&self) -> bool {
+        self.Seconds.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_Seconds(&mut self, v: i64) {
+        self.Seconds = ::std::option::Option::Some(v);
+    }
+
+    // optional fixed32 FractionalNanoseconds = 2;
+
+
+    pub fn get_FractionalNanoseconds(&self) -> u32 {
+        self.FractionalNanoseconds.unwrap_or(0)
+    }
+    pub fn clear_FractionalNanoseconds(&mut self) {
+        self.FractionalNanoseconds = ::std::option::Option::None;
+    }
+
+    pub fn has_FractionalNanoseconds(&self) -> bool {
+        self.FractionalNanoseconds.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_FractionalNanoseconds(&mut self, v: u32) {
+        self.FractionalNanoseconds = ::std::option::Option::Some(v);
+    }
+}
+
+impl ::protobuf::Message for UnixTime {
+    fn is_initialized(&self) -> bool {
+        if self.Seconds.is_none() {
+            return false;
+        }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.Seconds = ::std::option::Option::Some(tmp);
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeFixed32 {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_fixed32()?;
+                    self.FractionalNanoseconds = ::std::option::Option::Some(tmp);
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(v) = self.Seconds {
+            my_size += ::protobuf::rt::value_size(1, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.FractionalNanoseconds {
+            my_size += 5;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.Seconds {
+            os.write_int64(1, v)?;
+        }
+        if let Some(v) = self.FractionalNanoseconds {
+            os.write_fixed32(2, v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> UnixTime {
+        UnixTime::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "Seconds",
+                |m: &UnixTime| { &m.Seconds },
+                |m: &mut UnixTime| { &mut m.Seconds },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeFixed32>(
+                "FractionalNanoseconds",
+                |m: &UnixTime| { &m.FractionalNanoseconds },
+                |m: &mut UnixTime| { &mut m.FractionalNanoseconds },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<UnixTime>(
+                "UnixTime",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static UnixTime {
+        static instance: ::protobuf::rt::LazyV2<UnixTime> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(UnixTime::new)
+    }
+}
+
+impl ::protobuf::Clear for UnixTime {
+    fn clear(&mut self) {
+        self.Seconds = ::std::option::Option::None;
+        self.FractionalNanoseconds = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for UnixTime {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-static file_descriptor_proto_data: &'static [u8] = &[
-    0x0a, 0x0c, 0x75, 0x6e, 0x69, 0x78, 0x66, 0x73, 0x2e, 0x70, 0x72, 0x6f, 0x74, 0x6f, 0x12, 0x09,
-    0x75, 0x6e, 0x69, 0x78, 0x66, 0x73, 0x2e, 0x70, 0x62, 0x22, 0x9e, 0x01, 0x0a, 0x04, 0x44, 0x61,
-    0x74, 0x61, 0x12, 0x26, 0x0a, 0x04, 0x54, 0x79, 0x70, 0x65, 0x18, 0x01, 0x20, 0x02, 0x28, 0x0e,
-    0x32, 0x18, 0x2e, 0x75, 0x6e, 0x69, 0x78, 0x66, 0x73, 0x2e, 0x70, 0x62, 0x2e, 0x44, 0x61, 0x74,
-    0x61, 0x2e, 0x44, 0x61, 0x74, 0x61, 0x54, 0x79, 0x70, 0x65, 0x12, 0x0c, 0x0a, 0x04, 0x44, 0x61,
-    0x74, 0x61, 0x18, 0x02, 0x20, 0x01, 0x28, 0x0c, 0x12, 0x10, 0x0a, 0x08, 0x66, 0x69, 0x6c, 0x65,
-    0x73, 0x69, 0x7a, 0x65, 0x18, 0x03, 0x20, 0x01, 0x28, 0x04, 0x12, 0x12, 0x0a, 0x0a, 0x62, 0x6c,
-    0x6f, 0x63, 0x6b, 0x73, 0x69, 0x7a, 0x65, 0x73, 0x18, 0x04, 0x20, 0x03, 0x28, 0x04, 0x22, 0x3a,
-    0x0a, 0x08, 0x44, 0x61, 0x74, 0x61, 0x54, 0x79, 0x70, 0x65, 0x12, 0x07, 0x0a, 0x03, 0x52, 0x61,
-    0x77, 0x10, 0x00, 0x12, 0x0d, 0x0a, 0x09, 0x44, 0x69, 0x72, 0x65, 0x63, 0x74, 0x6f, 0x72, 0x79,
-    0x10, 0x01, 0x12, 0x08, 0x0a, 0x04, 0x46, 0x69, 0x6c, 0x65, 0x10, 0x02, 0x12, 0x0c, 0x0a, 0x08,
-    0x4d, 0x65, 0x74, 0x61, 0x64, 0x61, 0x74, 0x61, 0x10, 0x03, 0x22, 0x1c, 0x0a, 0x08, 0x4d, 0x65,
-    0x74, 0x61, 0x64, 0x61, 0x74, 0x61, 0x12, 0x10, 0x0a, 0x08, 0x4d, 0x69, 0x6d, 0x65, 0x54, 0x79,
-    0x70, 0x65, 0x18, 0x01, 0x20, 0x02, 0x28, 0x09, 0x4a, 0xf3, 0x04, 0x0a, 0x06, 0x12, 0x04, 0x00,
-    0x00, 0x12, 0x01, 0x0a, 0x08, 0x0a, 0x01, 0x02, 0x12, 0x03, 0x00, 0x08, 0x11, 0x0a, 0x0a, 0x0a,
-    0x02, 0x04, 0x00, 0x12, 0x04, 0x02, 0x00, 0x0e, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x00, 0x01,
-    0x12, 0x03, 0x02, 0x08, 0x0c, 0x0a, 0x0c, 0x0a, 0x04, 0x04, 0x00, 0x04, 0x00, 0x12, 0x04, 0x03,
-    0x08, 0x08, 0x09, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x04, 0x00, 0x01, 0x12, 0x03, 0x03, 0x0d,
-    0x15, 0x0a, 0x0d, 0x0a, 0x06, 0x04, 0x00, 0x04, 0x00, 0x02, 0x00, 0x12, 0x03, 0x04, 0x10, 0x18,
-    0x0a, 0x0e, 0x0a, 0x07, 0x04, 0x00, 0x04, 0x00, 0x02, 0x00, 0x01, 0x12, 0x03, 0x04, 0x10, 0x13,
-    0x0a, 0x0e, 0x0a, 0x07, 0x04, 0x00, 0x04, 0x00, 0x02, 0x00, 0x02, 0x12, 0x03, 0x04, 0x16, 0x17,
-    0x0a, 0x0d, 0x0a, 0x06, 0x04, 0x00, 0x04, 0x00, 0x02, 0x01, 0x12, 0x03, 0x05, 0x10, 0x1e, 0x0a,
-    0x0e, 0x0a, 0x07, 0x04, 0x00, 0x04, 0x00, 0x02, 0x01, 0x01, 0x12, 0x03, 0x05, 0x10, 0x19, 0x0a,
-    0x0e, 0x0a, 0x07, 0x04, 0x00, 0x04, 0x00, 0x02, 0x01, 0x02, 0x12, 0x03, 0x05, 0x1c, 0x1d, 0x0a,
-    0x0d, 0x0a, 0x06, 0x04, 0x00, 0x04, 0x00, 0x02, 0x02, 0x12, 0x03, 0x06, 0x10, 0x19, 0x0a, 0x0e,
-    0x0a, 0x07, 0x04, 0x00, 0x04, 0x00, 0x02, 0x02, 0x01, 0x12, 0x03, 0x06, 0x10, 0x14, 0x0a, 0x0e,
-    0x0a, 0x07, 0x04, 0x00, 0x04, 0x00, 0x02, 0x02, 0x02, 0x12, 0x03, 0x06, 0x17, 0x18, 0x0a, 0x0d,
-    0x0a, 0x06, 0x04, 0x00, 0x04, 0x00, 0x02, 0x03, 0x12, 0x03, 0x07, 0x10, 0x1d, 0x0a, 0x0e, 0x0a,
-    0x07, 0x04, 0x00, 0x04, 0x00, 0x02, 0x03, 0x01, 0x12, 0x03, 0x07, 0x10, 0x18, 0x0a, 0x0e, 0x0a,
-    0x07, 0x04, 0x00, 0x04, 0x00, 0x02, 0x03, 0x02, 0x12, 0x03, 0x07, 0x1b, 0x1c, 0x0a, 0x0b, 0x0a,
-    0x04, 0x04, 0x00, 0x02, 0x00, 0x12, 0x03, 0x0a, 0x08, 0x23, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00,
-    0x02, 0x00, 0x04, 0x12, 0x03, 0x0a, 0x08, 0x10, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x00,
-    0x06, 0x12, 0x03, 0x0a, 0x11, 0x19, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x00, 0x01, 0x12,
-    0x03, 0x0a, 0x1a, 0x1e, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x00, 0x03, 0x12, 0x03, 0x0a,
-    0x21, 0x22, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x01, 0x12, 0x03, 0x0b, 0x08, 0x20, 0x0a,
-    0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x01, 0x04, 0x12, 0x03, 0x0b, 0x08, 0x10, 0x0a, 0x0c, 0x0a,
-    0x05, 0x04, 0x00, 0x02, 0x01, 0x05, 0x12, 0x03, 0x0b, 0x11, 0x16, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
-    0x00, 0x02, 0x01, 0x01, 0x12, 0x03, 0x0b, 0x17, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02,
-    0x01, 0x03, 0x12, 0x03, 0x0b, 0x1e, 0x1f, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x02, 0x12,
-    0x03, 0x0c, 0x08, 0x25, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x04, 0x12, 0x03, 0x0c,
-    0x08, 0x10, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x05, 0x12, 0x03, 0x0c, 0x11, 0x17,
-    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x01, 0x12, 0x03, 0x0c, 0x18, 0x20, 0x0a, 0x0c,
-    0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x03, 0x12, 0x03, 0x0c, 0x23, 0x24, 0x0a, 0x0b, 0x0a, 0x04,
-    0x04, 0x00, 0x02, 0x03, 0x12, 0x03, 0x0d, 0x08, 0x27, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02,
-    0x03, 0x04, 0x12, 0x03, 0x0d, 0x08, 0x10, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x03, 0x05,
-    0x12, 0x03, 0x0d, 0x11, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x03, 0x01, 0x12, 0x03,
-    0x0d, 0x18, 0x22, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x03, 0x03, 0x12, 0x03, 0x0d, 0x25,
-    0x26, 0x0a, 0x0a, 0x0a, 0x02, 0x04, 0x01, 0x12, 0x04, 0x10, 0x00, 0x12, 0x01, 0x0a, 0x0a, 0x0a,
-    0x03, 0x04, 0x01, 0x01, 0x12, 0x03, 0x10, 0x08, 0x10, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x01, 0x02,
-    0x00, 0x12, 0x03, 0x11, 0x08, 0x25, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x04, 0x12,
-    0x03, 0x11, 0x08, 0x10, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x05, 0x12, 0x03, 0x11,
-    0x11, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x01, 0x12, 0x03, 0x11, 0x18, 0x20,
-    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x03, 0x12, 0x03, 0x11, 0x23, 0x24,
-];
-
-static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
-    lock: ::protobuf::lazy::ONCE_INIT,
-    ptr: 0 as *const ::protobuf::descriptor::FileDescriptorProto,
-};
+impl ::protobuf::reflect::ProtobufValue for UnixTime {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x0cunixfs.proto\x12\tunixfs.pb\"\xe3\x02\n\x04Data\x12.\n\x04Type\x18\
+    \x01\x20\x02(\x0e2\x18.unixfs.pb.Data.DataTypeR\x04TypeB\0\x12\x14\n\x04\
+    Data\x18\x02\x20\x01(\x0cR\x04DataB\0\x12\x1c\n\x08filesize\x18\x03\x20\
+    \x01(\x04R\x08filesizeB\0\x12\x20\n\nblocksizes\x18\x04\x20\x03(\x04R\nb\
+    locksizesB\0\x12\x1c\n\x08hashType\x18\x05\x20\x01(\x04R\x08hashTypeB\0\
+    \x12\x18\n\x06fanout\x18\x06\x20\x01(\x04R\x06fanoutB\0\x12\x14\n\x04mod\
+    e\x18\x07\x20\x01(\rR\x04modeB\0\x12+\n\x05mtime\x18\x08\x20\x01(\x0b2\
+    \x13.unixfs.pb.UnixTimeR\x05mtimeB\0\"X\n\x08DataType\x12\x07\n\x03Raw\
+    \x10\0\x12\r\n\tDirectory\x10\x01\x12\x08\n\x04File\x10\x02\x12\x0c\n\
+    \x08Metadata\x10\x03\x12\x0b\n\x07Symlink\x10\x04\x12\r\n\tHAMTShard\x10\
+    \x05\x1a\0:\0\"*\n\x08Metadata\x12\x1c\n\x08MimeType\x18\x01\x20\x02(\tR\
+    \x08MimeTypeB\0:\0\"`\n\x08UnixTime\x12\x1a\n\x07Seconds\x18\x01\x20\x02\
+    (\x03R\x07SecondsB\0\x126\n\x15FractionalNanoseconds\x18\x02\x20\x01(\
+    \x07R\x15FractionalNanosecondsB\0:\0B\0b\x06proto2\
+";
+
+static file_descriptor_proto_lazy: ::protobuf::rt::LazyV2<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::LazyV2::INIT;
 
 fn parse_descriptor_proto() -> ::protobuf::descriptor::FileDescriptorProto {
-    ::protobuf::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
 }
 
 pub fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
-    unsafe {
-        file_descriptor_proto_lazy.get(|| {
-            parse_descriptor_proto()
-        })
-    }
+    file_descriptor_proto_lazy.get(|| {
+        parse_descriptor_proto()
+    })
 }
@@ -0,0 +1,49 @@
+//! Unsigned LEB128 varints as used by multiformats (multihash, multicodec,
+//! CID versions).
+
+/// Appends the varint encoding of `n` to `buf`.
+pub fn encode(mut n: u64, buf: &mut Vec<u8>) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+/// Decodes a varint from the start of `bytes`, returning the value and the
+/// number of bytes it used. Multiformats cap varints at nine bytes and
+/// forbid non-minimal encodings; both are rejected.
+pub fn decode(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut n: u64 = 0;
+    for (i, &byte) in bytes.iter().enumerate().take(9) {
+        n |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 {
+                return None;
+            }
+            return Some((n, i + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        for &n in &[0, 1, 0x7f, 0x80, 0x12, 0x0129, 0xb220, 300, 1 << 62] {
+            let mut buf = Vec::new();
+            encode(n, &mut buf);
+            assert_eq!(decode(&buf), Some((n, buf.len())));
+        }
+    }
+
+    #[test]
+    fn rejects_non_minimal_encodings() {
+        assert_eq!(decode(&[0x81, 0x00]), None);
+        assert_eq!(decode(&[0x80]), None);
+    }
+}